@@ -0,0 +1,101 @@
+// Node throughput of the two ways of walking the game tree: cloning the
+// whole `Quoridor` for every child (what the strategies used to do) versus
+// making and unmaking moves in place on one working copy, plus the same walk
+// and the shortest-path query on the `Copy` bitboard representation.
+//
+// Run with `cargo bench --bench search_throughput`.
+use std::time::{Duration, Instant};
+
+use quoridor::main::bitboard::BitboardGame;
+use quoridor::main::{MinimaxStrategy, Player, Quoridor, RuleSet, Strategy, DEFAULT_TABLE_MB};
+
+// A quiet middlegame with a few walls down, so wall generation is realistic
+const POSITION: &str = "c3f6 / e3 / e4 e6 / 8 9 / 1";
+
+fn perft_clone(game: &Quoridor, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    
+    let mut nodes = 0;
+    for mv in game.legal_moves() {
+        let mut child = game.clone();
+        child.make_move(mv);
+        nodes += perft_clone(&child, depth - 1);
+    }
+    nodes
+}
+
+fn perft_make_unmake(game: &mut Quoridor, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    
+    let mut nodes = 0;
+    for mv in game.legal_moves() {
+        game.make_move(mv);
+        nodes += perft_make_unmake(game, depth - 1);
+        game.unmake_move();
+    }
+    nodes
+}
+
+fn perft_bitboard(game: &BitboardGame, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    
+    let mut nodes = 0;
+    for mv in game.legal_moves() {
+        let mut child = *game;
+        child.make_move(mv);
+        nodes += perft_bitboard(&child, depth - 1);
+    }
+    nodes
+}
+
+fn report(label: &str, nodes: usize, elapsed: Duration) {
+    println!(
+        "{:<24} {:>9} nodes in {:>9.2?}  ({:>10.0} nodes/s)",
+        label, nodes, elapsed, nodes as f64 / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some(POSITION)).expect("benchmark position is valid");
+    let depth = 2;
+    
+    let start = Instant::now();
+    let nodes = perft_clone(&game, depth);
+    report("perft clone-per-node", nodes, start.elapsed());
+    
+    let mut working = game.clone();
+    let start = Instant::now();
+    let nodes = perft_make_unmake(&mut working, depth);
+    report("perft make/unmake", nodes, start.elapsed());
+    assert_eq!(
+        (working.position_key(), &working.pawn_positions, &working.hwall_positions, &working.vwall_positions, &working.walls_available),
+        (game.position_key(), &game.pawn_positions, &game.hwall_positions, &game.vwall_positions, &game.walls_available),
+        "make/unmake must restore the position"
+    );
+    
+    let board = BitboardGame::from_quoridor(&game).expect("benchmark board fits in a bitboard");
+    let start = Instant::now();
+    let nodes = perft_bitboard(&board, depth);
+    report("perft bitboard", nodes, start.elapsed());
+    
+    // The graph keeps its distance fields between queries, so timing it here
+    // would time a lookup; only the bitboard's flood fill is measured
+    let queries = 20_000;
+    let start = Instant::now();
+    let total: usize = (0..queries).map(|_| board.distance_to_goal(Player::Player1)).sum();
+    report("distance bitboard", queries, start.elapsed());
+    assert_eq!(total, queries * game.distance_to_goal(Player::Player1), "both boards must agree on distances");
+    
+    for (depth, table_mb) in [(2, 0), (3, 0), (3, DEFAULT_TABLE_MB), (4, DEFAULT_TABLE_MB)] {
+        let mut minimax = MinimaxStrategy::new("", Vec::new(), depth, table_mb);
+        let start = Instant::now();
+        let mv = minimax.choose_move(&game);
+        println!("Minimax{} ({} MB table) chose {:?} in {:.2?}", depth, table_mb, mv.map(|m| m.to_string()), start.elapsed());
+    }
+}
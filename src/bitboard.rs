@@ -0,0 +1,376 @@
+// Bitboard-backed game state for two-player games on boards up to 11x11.
+//
+// The square on rank r (from 1) and file f is bit `(r - 1) * size + f` of a
+// u128, so north is a shift up by a row's width. Instead of a graph,
+// the board keeps one mask per direction of the squares a pawn can step out
+// of in that direction, so a breadth-first search is a handful of shifts and
+// ands per layer rather than a Dijkstra run over petgraph nodes. The whole
+// state is `Copy`, which makes copy-make search as cheap as make/unmake.
+//
+// The rules are the same as `Quoridor`'s under the standard `RuleSet`: the
+// same pawn moves (including straight and diagonal jumps), the same wall
+// overlap and path checks, and the same 100 sentinel for an unreachable goal.
+use super::{max_walls, Coord, Move, Orientation, Player, Quoridor, QuoridorError, RuleSet, Square, MIN_BOARD_SIZE};
+
+// Largest board whose squares fit in a u128
+pub const MAX_BITBOARD_SIZE: usize = 11;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitboardGame {
+    pub size: usize,
+    // Squares a pawn may step out of in each direction (on the board and not walled off)
+    open_north: u128,
+    open_south: u128,
+    open_east: u128,
+    open_west: u128,
+    // Wall centres, by the anchor square the wall is named after
+    pub hwalls: u128,
+    pub vwalls: u128,
+    pub pawns: [usize; 2],
+    pub walls_available: [usize; 2],
+    pub active_player: Player,
+}
+
+impl BitboardGame {
+    pub fn new(size: usize, walls: usize) -> Result<Self, QuoridorError> {
+        let mut game = Self::empty(size)?;
+        if walls > max_walls(size, RuleSet::default()) {
+            return Err(QuoridorError::TooManyWalls(walls, size));
+        }
+        game.pawns = [game.square((size - 1, (size - 1) / 2)), game.square((0, size / 2))];
+        game.walls_available = [walls, walls];
+        Ok(game)
+    }
+
+    // Same position as a petgraph-backed game
+    pub fn from_quoridor(game: &Quoridor) -> Result<Self, QuoridorError> {
+        if game.players.len() != 2 {
+            return Err(QuoridorError::UnsupportedPlayerCount(game.players.len()));
+        }
+        // Pawns can start anywhere, but goals, walls and wall counts are the
+        // standard ones; draws are left to the caller
+        let playable = RuleSet { start: game.rules.start, move_limit: game.rules.move_limit, ..RuleSet::default() };
+        if game.rules != playable {
+            return Err(QuoridorError::UnsupportedRules(format!("{} on a bitboard", game.rules)));
+        }
+        let mut board = Self::empty(game.size)?;
+        for &coord in &game.hwall_positions {
+            board.place_wall(coord, Orientation::Horizontal);
+        }
+        for &coord in &game.vwall_positions {
+            board.place_wall(coord, Orientation::Vertical);
+        }
+        for player in [Player::Player1, Player::Player2] {
+            board.pawns[player.index()] = board.square(game.pawn_positions[&player]);
+            board.walls_available[player.index()] = game.walls_available[&player];
+        }
+        board.active_player = game.active_player;
+        Ok(board)
+    }
+
+    // Open board with no pawns placed or walls in hand
+    fn empty(size: usize) -> Result<Self, QuoridorError> {
+        if !(MIN_BOARD_SIZE..=MAX_BITBOARD_SIZE).contains(&size) {
+            return Err(QuoridorError::UnsupportedBoardSize(size));
+        }
+
+        let all = (1u128 << (size * size)) - 1;
+        let top_row = Self::row_mask(size, size - 1);
+        let bottom_row = Self::row_mask(size, 0);
+        let first_col = (0..size).fold(0u128, |mask, row| mask | 1 << (row * size));
+        let last_col = first_col << (size - 1);
+
+        Ok(BitboardGame {
+            size,
+            open_north: all & !top_row,
+            open_south: all & !bottom_row,
+            open_east: all & !last_col,
+            open_west: all & !first_col,
+            hwalls: 0,
+            vwalls: 0,
+            pawns: [0, 0],
+            walls_available: [0, 0],
+            active_player: Player::Player1,
+        })
+    }
+
+    fn row_mask(size: usize, row: usize) -> u128 {
+        ((1u128 << size) - 1) << (row * size)
+    }
+
+    pub fn square(&self, (row, col): Coord) -> usize {
+        (self.size - 1 - row) * self.size + col
+    }
+
+    pub fn coord(&self, square: usize) -> Coord {
+        (self.size - 1 - square / self.size, square % self.size)
+    }
+
+    // Bit index of a named square, which must be on the board
+    pub fn index_of(&self, square: Square) -> usize {
+        (square.rank - 1) * self.size + square.file
+    }
+
+    // Name of the square at a bit index
+    pub fn square_at(&self, square: usize) -> Square {
+        Square::new(square % self.size, square / self.size + 1)
+    }
+
+    pub fn goal_mask(&self, player: Player) -> u128 {
+        match player {
+            Player::Player1 => Self::row_mask(self.size, self.size - 1),
+            Player::Player2 => Self::row_mask(self.size, 0),
+            Player::Player3 | Player::Player4 => unreachable!("bitboard games have two players"),
+        }
+    }
+
+    // Every square one open step away from a square in `from`
+    fn neighbours(&self, from: u128) -> u128 {
+        ((from & self.open_north) << self.size)
+            | ((from & self.open_south) >> self.size)
+            | ((from & self.open_east) << 1)
+            | ((from & self.open_west) >> 1)
+    }
+
+    // Flood fill: every square connected to a square in `from`
+    pub fn reachable(&self, from: u128) -> u128 {
+        let mut seen = from;
+        loop {
+            let next = seen | self.neighbours(seen);
+            if next == seen {
+                return seen;
+            }
+            seen = next;
+        }
+    }
+
+    // Breadth-first distance from `from` to the nearest square in `target`, one
+    // layer per iteration
+    fn distance(&self, from: u128, target: u128) -> usize {
+        let mut seen = from;
+        let mut frontier = from;
+        let mut steps = 0;
+        while frontier & target == 0 {
+            frontier = self.neighbours(frontier) & !seen;
+            if frontier == 0 {
+                return 100;
+            }
+            seen |= frontier;
+            steps += 1;
+        }
+        steps
+    }
+
+    pub fn distance_to_goal(&self, player: Player) -> usize {
+        self.distance(1 << self.pawns[player.index()], self.goal_mask(player))
+    }
+
+    pub fn moves_to_next_row(&self, player: Player) -> usize {
+        let row = self.pawns[player.index()] / self.size;
+        let next_row = match player {
+            Player::Player1 if row < self.size - 1 => row + 1,
+            Player::Player2 if row > 0 => row - 1,
+            Player::Player3 | Player::Player4 => unreachable!("bitboard games have two players"),
+            _ => return 100,
+        };
+        self.distance(1 << self.pawns[player.index()], Self::row_mask(self.size, next_row))
+    }
+
+    pub fn has_path_to_goal(&self, player: Player) -> bool {
+        self.reachable(1 << self.pawns[player.index()]) & self.goal_mask(player) != 0
+    }
+
+    // Squares reachable in one step from `square` (walls and edges respected),
+    // paired with the step as a (row, col) offset
+    fn steps_from(&self, square: usize) -> impl Iterator<Item = (usize, (isize, isize))> + '_ {
+        let bit = 1u128 << square;
+        let size = self.size;
+        [
+            (self.open_north, size as isize, (1, 0)),
+            (self.open_south, -(size as isize), (-1, 0)),
+            (self.open_east, 1, (0, 1)),
+            (self.open_west, -1, (0, -1)),
+        ]
+        .into_iter()
+        .filter(move |(open, _, _)| open & bit != 0)
+        .map(move |(_, shift, step)| ((square as isize + shift) as usize, step))
+    }
+
+    pub fn get_legal_moves(&self, player: Player) -> Vec<Move> {
+        let own = self.pawns[player.index()];
+        let opponent = self.pawns[player.opponent().index()];
+        let mut legal_moves = Vec::new();
+
+        for (neighbour, direction) in self.steps_from(own) {
+            if neighbour != opponent {
+                legal_moves.push(Move::Pawn(self.square_at(neighbour)));
+                continue;
+            }
+
+            // Jump straight over if nothing is behind the opponent, otherwise
+            // step diagonally to either side of them
+            match self.steps_from(opponent).find(|&(_, step)| step == direction) {
+                Some((jump, _)) => legal_moves.push(Move::Pawn(self.square_at(jump))),
+                None => {
+                    for (square, _) in self.steps_from(opponent) {
+                        if square != own {
+                            legal_moves.push(Move::Pawn(self.square_at(square)));
+                        }
+                    }
+                },
+            }
+        }
+
+        legal_moves
+    }
+
+    // Cut the two edges a wall runs along
+    fn close_edges(&mut self, (row, col): Coord, orientation: Orientation) {
+        let anchor = 1u128 << self.square((row, col));
+        match orientation {
+            Orientation::Horizontal => {
+                let lower = anchor | anchor << 1;
+                self.open_north &= !lower;
+                self.open_south &= !(lower << self.size);
+            },
+            Orientation::Vertical => {
+                let left = anchor | anchor << self.size;
+                self.open_east &= !left;
+                self.open_west &= !(left << 1);
+            },
+        }
+    }
+
+    fn edges_open(&self, (row, col): Coord, orientation: Orientation) -> bool {
+        let anchor = 1u128 << self.square((row, col));
+        match orientation {
+            Orientation::Horizontal => {
+                let lower = anchor | anchor << 1;
+                self.open_north & lower == lower
+            },
+            Orientation::Vertical => {
+                let left = anchor | anchor << self.size;
+                self.open_east & left == left
+            },
+        }
+    }
+
+    // Put a wall down without checking it or spending a player's wall
+    pub fn place_wall(&mut self, coord: Coord, orientation: Orientation) {
+        let anchor = 1u128 << self.square(coord);
+        match orientation {
+            Orientation::Horizontal => self.hwalls |= anchor,
+            Orientation::Vertical => self.vwalls |= anchor,
+        }
+        self.close_edges(coord, orientation);
+    }
+
+    pub fn wall_check(&self, player: Player, coord: Coord, orientation: Orientation) -> Result<(), QuoridorError> {
+        let wall = Move::Wall(Square::at(self.size, coord), orientation);
+        if !self.anchor_on_board(coord) {
+            return Err(QuoridorError::OutOfBounds(wall));
+        }
+
+        if self.walls_available[player.index()] == 0 {
+            return Err(QuoridorError::NoWallsLeft(player));
+        }
+
+        if !self.wall_fits(coord, orientation) {
+            return Err(QuoridorError::WallOverlap(wall));
+        }
+
+        let mut after = *self;
+        after.close_edges(coord, orientation);
+        if !after.has_path_to_goal(Player::Player1) || !after.has_path_to_goal(Player::Player2) {
+            return Err(QuoridorError::PathBlocked(wall));
+        }
+
+        Ok(())
+    }
+
+    // Whether a wall is on the board and clear of the others: no crossing wall
+    // on the same centre and no edge already cut by a parallel wall. Says
+    // nothing about the paths it leaves.
+    pub fn wall_fits(&self, coord: Coord, orientation: Orientation) -> bool {
+        if !self.anchor_on_board(coord) {
+            return false;
+        }
+        let crossing = match orientation {
+            Orientation::Horizontal => self.vwalls,
+            Orientation::Vertical => self.hwalls,
+        };
+        crossing & (1 << self.square(coord)) == 0 && self.edges_open(coord, orientation)
+    }
+
+    // Walls are anchored below and left of their centre, so off the top row
+    // and the last file
+    fn anchor_on_board(&self, (row, col): Coord) -> bool {
+        (1..self.size).contains(&row) && col + 1 < self.size
+    }
+
+    pub fn get_legal_walls(&self, player: Player) -> Vec<Move> {
+        let mut legal_walls = Vec::new();
+        for row in 1..self.size {
+            for col in 0..(self.size - 1) {
+                for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+                    if self.wall_check(player, (row, col), orientation).is_ok() {
+                        legal_walls.push(Move::Wall(Square::at(self.size, (row, col)), orientation));
+                    }
+                }
+            }
+        }
+        legal_walls
+    }
+
+    // All legal moves for the active player: pawn moves first, then walls
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let player = self.active_player;
+        let mut moves = self.get_legal_moves(player);
+        if self.walls_available[player.index()] > 0 {
+            moves.extend(self.get_legal_walls(player));
+        }
+        moves
+    }
+
+    // Whether the move takes the active player's pawn onto its goal row
+    pub fn win_check(&self, mv: Move) -> bool {
+        match mv {
+            Move::Pawn(destination) => self.goal_mask(self.active_player) & (1 << self.index_of(destination)) != 0,
+            Move::Wall(..) => false,
+        }
+    }
+
+    // Play a move already known to be legal. The state is `Copy`, so search
+    // code takes back a move by keeping the previous value.
+    pub fn make_move(&mut self, mv: Move) {
+        let player = self.active_player;
+        match mv {
+            Move::Pawn(destination) => self.pawns[player.index()] = self.index_of(destination),
+            Move::Wall(square, orientation) => {
+                self.place_wall(self.coord(self.index_of(square)), orientation);
+                self.walls_available[player.index()] -= 1;
+            },
+        }
+        self.active_player = player.opponent();
+    }
+
+    // Play a move for the active player, rejecting it if it is not legal
+    pub fn apply(&mut self, mv: Move) -> Result<(), QuoridorError> {
+        match mv {
+            Move::Pawn(destination) => {
+                if destination.coord(self.size).is_none() {
+                    return Err(QuoridorError::OutOfBounds(mv));
+                }
+                if !self.get_legal_moves(self.active_player).contains(&mv) {
+                    return Err(QuoridorError::IllegalMove(mv));
+                }
+            },
+            Move::Wall(square, orientation) => match square.coord(self.size) {
+                Some(coord) => self.wall_check(self.active_player, coord, orientation)?,
+                None => return Err(QuoridorError::OutOfBounds(mv)),
+            },
+        }
+        self.make_move(mv);
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+// Exact play once the walls are gone.
+//
+// With no walls left on either side, the board can no longer change and the
+// game is a race between two pawns that may jump each other. A race has at
+// most size^4 * 2 positions (both squares and the side to move), so it is
+// solved outright by retrograde analysis: start from the positions where a
+// pawn has already arrived, which the side to move has lost, and work
+// backwards. A position is won once some move reaches a lost one, and lost
+// once every move reaches a won one; whatever is never settled lets both
+// sides keep away from a loss forever and is a draw. Working outwards one
+// move at a time settles each position at its shortest win or longest loss.
+//
+// The solution ignores the rules' move limit and the repetition rule, and a
+// side with no move at all loses, as in a tournament.
+//
+// Solving covers every race on the board's walls at once, and the walls stay
+// put for the rest of the game, so strategies keep solutions in a
+// `PawnRaceCache` and solve again only when the wall layout is new. A search
+// that places the last walls meets a new layout for each of them, so the
+// cache keeps a few.
+use std::collections::{HashMap, VecDeque};
+
+use petgraph::graph::NodeIndex;
+
+use super::{DrawReason, GameResult, Move, Quoridor, RuleSet};
+
+// The outcome of a pawn race with best play from both sides, and the line
+// that reaches it: to the goal for a win, or just the first move of a draw
+#[derive(Debug, Clone, PartialEq)]
+pub struct PawnRace {
+    pub result: GameResult,
+    pub line: Vec<Move>,
+}
+
+impl PawnRace {
+    pub fn best_move(&self) -> Option<Move> {
+        self.line.first().copied()
+    }
+
+    // Moves until the race is over, for both sides together; none for a draw
+    pub fn plies(&self) -> usize {
+        match self.result {
+            GameResult::Win(_) => self.line.len(),
+            GameResult::Draw(_) => 0,
+        }
+    }
+}
+
+// Whether the side to move wins, and after how many moves; small, as a
+// cache holds many tables
+type Value = Option<(bool, u16)>;
+
+// Layouts a cache keeps solved before it starts over
+const CACHED_LAYOUTS: usize = 256;
+
+// Every race on one wall layout, solved: a value per position, indexed by
+// `Quoridor::race_state`
+#[derive(Debug, Clone)]
+pub struct PawnRaceTable {
+    values: Vec<Value>,
+}
+
+// The races solved on recent wall layouts, by board size, rules and `wall_key`
+#[derive(Debug, Clone, Default)]
+pub struct PawnRaceCache {
+    tables: HashMap<(usize, RuleSet, u64), PawnRaceTable>,
+}
+
+impl PawnRaceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // As `Quoridor::solve_pawn_race`, solving again only for a new layout
+    pub fn solve(&mut self, game: &Quoridor) -> Option<PawnRace> {
+        let table = self.table(game)?;
+        game.play_pawn_race(table)
+    }
+
+    // The race's result and the moves until it is over, as in `PawnRace`,
+    // without following the line
+    pub fn result(&mut self, game: &Quoridor) -> Option<(GameResult, usize)> {
+        let table = self.table(game)?;
+        game.race_result(table)
+    }
+
+    fn table(&mut self, game: &Quoridor) -> Option<&PawnRaceTable> {
+        if !game.is_pawn_race() {
+            return None;
+        }
+        let layout = game.race_layout();
+        if self.tables.len() >= CACHED_LAYOUTS && !self.tables.contains_key(&layout) {
+            self.tables.clear();
+        }
+        Some(self.tables.entry(layout).or_insert_with(|| game.pawn_race_table()))
+    }
+}
+
+impl Quoridor {
+    // The exact result of a two-player game in which neither side has a
+    // wall left; none while walls can still be placed or the game is over
+    pub fn solve_pawn_race(&self) -> Option<PawnRace> {
+        if !self.is_pawn_race() {
+            return None;
+        }
+        self.play_pawn_race(&self.pawn_race_table())
+    }
+
+    fn is_pawn_race(&self) -> bool {
+        self.players.len() == 2 && self.outcome.is_none() && self.walls_available.values().all(|&left| left == 0)
+    }
+
+    fn race_layout(&self) -> (usize, RuleSet, u64) {
+        (self.size, self.rules, self.wall_key())
+    }
+
+    // Positions by both squares (in turn order) and the side to move
+    fn race_state(&self, pawns: [usize; 2], side: usize) -> usize {
+        (pawns[0] * self.graph.node_count() + pawns[1]) * 2 + side
+    }
+
+    // The side's moves, and the positions they lead to
+    fn race_successors(&self, pawns: [usize; 2], side: usize) -> Vec<(Move, usize)> {
+        let square = |index: usize| self.graph[NodeIndex::new(index)];
+        self.pawn_moves(square(pawns[side]), &[square(pawns[1 - side])]).into_iter()
+            .map(|mv| {
+                let mut after = pawns;
+                after[side] = self.node_indices[&self.move_coord(mv)].index();
+                (mv, self.race_state(after, 1 - side))
+            })
+            .collect()
+    }
+
+    // Every race on the board's walls, pawns anywhere, solved
+    fn pawn_race_table(&self) -> PawnRaceTable {
+        let squares = self.graph.node_count();
+        let arrived: Vec<Vec<bool>> = self.players.iter()
+            .map(|player| self.graph.node_indices().map(|node| self.goal_positions[player].contains(&self.graph[node])).collect())
+            .collect();
+
+        let states = squares * squares * 2;
+        let mut values: Vec<Value> = vec![None; states];
+        let mut unsettled = vec![0; states];
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); states];
+        let mut queue = VecDeque::new();
+        for first in 0..squares {
+            for second in (0..squares).filter(|&second| second != first) {
+                let pawns = [first, second];
+                for side in 0..2 {
+                    let position = self.race_state(pawns, side);
+                    if arrived[0][first] || arrived[1][second] {
+                        values[position] = Some((false, 0));
+                        queue.push_back(position);
+                        continue;
+                    }
+                    let next = self.race_successors(pawns, side);
+                    if next.is_empty() {
+                        values[position] = Some((false, 0));
+                        queue.push_back(position);
+                    }
+                    unsettled[position] = next.len();
+                    for (_, after) in next {
+                        predecessors[after].push(position);
+                    }
+                }
+            }
+        }
+
+        while let Some(position) = queue.pop_front() {
+            let Some((wins, plies)) = values[position] else {
+                unreachable!("only settled positions are queued");
+            };
+            for &before in &predecessors[position] {
+                if values[before].is_some() {
+                    continue;
+                }
+                if !wins {
+                    values[before] = Some((true, plies + 1));
+                    queue.push_back(before);
+                } else {
+                    unsettled[before] -= 1;
+                    if unsettled[before] == 0 {
+                        values[before] = Some((false, plies + 1));
+                        queue.push_back(before);
+                    }
+                }
+            }
+        }
+
+        PawnRaceTable { values }
+    }
+
+    // The race from the current position, read off the table
+    fn play_pawn_race(&self, table: &PawnRaceTable) -> Option<PawnRace> {
+        let values = &table.values;
+
+        // Follow the best moves: the quickest win, the slowest loss, or any
+        // move that keeps a draw
+        let side = self.players.iter().position(|&player| player == self.active_player)?;
+        let root = [0, 1].map(|index| self.node_indices[&self.pawn_positions[&self.players[index]]].index());
+        let mut pawns = root;
+        let mut to_move = side;
+        let mut line = Vec::new();
+        loop {
+            let value = values[self.race_state(pawns, to_move)];
+            let best = self.race_successors(pawns, to_move).into_iter()
+                .filter(|&(_, after)| match (value, values[after]) {
+                    (Some((true, plies)), Some((false, left))) => left + 1 == plies,
+                    (Some((false, plies)), Some((true, left))) => left + 1 == plies,
+                    (None, after) => after.is_none(),
+                    _ => false,
+                })
+                .map(|(mv, _)| mv)
+                .next();
+            let Some(mv) = best else {
+                break;
+            };
+            line.push(mv);
+            pawns[to_move] = self.node_indices[&self.move_coord(mv)].index();
+            to_move = 1 - to_move;
+            if value.is_none() {
+                break;
+            }
+        }
+
+        let (result, _) = self.race_result(table)?;
+        Some(PawnRace { result, line })
+    }
+
+    // The result from the current position and the moves until it, read off
+    // the table
+    fn race_result(&self, table: &PawnRaceTable) -> Option<(GameResult, usize)> {
+        let side = self.players.iter().position(|&player| player == self.active_player)?;
+        let pawns = [0, 1].map(|index| self.node_indices[&self.pawn_positions[&self.players[index]]].index());
+        Some(match table.values[self.race_state(pawns, side)] {
+            Some((true, plies)) => (GameResult::Win(self.active_player), plies as usize),
+            Some((false, plies)) => (GameResult::Win(self.players[1 - side]), plies as usize),
+            None => (GameResult::Draw(DrawReason::Repetition), 0),
+        })
+    }
+}
@@ -0,0 +1,232 @@
+// Position evaluation, apart from the search that uses it.
+//
+// An evaluator scores a position for one player, higher when they are
+// ahead. Each search strategy takes any `Box<dyn Evaluator>`, so heuristics
+// can be compared with the search held fixed: Minimax defaults to the
+// paper's C3 features, Simulated Annealing to its own weighted features,
+// and Adaptive and the MCTS playouts to the difference in path lengths.
+//
+// Evaluators are named, and parsed back from their names, in strategy names
+// after an '@': "Minimax2@PathDifference", "MCTS1000@C3" or
+// "Adaptive@0.6*path_difference+6.52*wall_difference".
+use std::fmt;
+use std::str::FromStr;
+
+use super::{EvalWeights, Player, Quoridor, QuoridorError};
+
+pub trait Evaluator {
+    fn name(&self) -> String;
+    fn evaluate(&self, game: &Quoridor, player: Player) -> f64;
+}
+
+// Facts about a position for one player that evaluators weigh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    PathDifference,   // Opponent's distance to goal minus the player's
+    PlayerDistance,
+    OpponentDistance,
+    WallDifference,   // Player's walls left minus the opponent's
+    PlayerWalls,
+    OpponentWalls,
+    Attacking,        // C3's f3: the inverse of the player's steps to their next row (20 if none)
+    Defensive,        // C3's f4: the opponent's steps to their next row
+    NextRowProgress,  // 1 / (steps to the next row + 0.1), as Simulated Annealing weighs it
+    // From `features`, dearer at a search leaf: the wall ones search again
+    // for each wall across every shortest path, and the race is played out
+    WallThreat,       // Most one opponent wall could add to the player's distance (0 if they have none)
+    WallOpportunity,  // Most one of the player's walls could add to the opponent's (0 if the player has none)
+    PathMultiplicity, // log2 of the number of the player's shortest paths
+    DisjointPaths,    // The player's shortest paths that share no step
+    ChokePoints,      // Squares all the player's shortest paths pass through
+    RaceTempo,        // Moves the player wins a wall-less race by, negative if losing
+    WallParity,       // 1 if the player would place the last wall, -1 if the opponent would
+}
+
+impl Feature {
+    pub const ALL: [Feature; 16] = [
+        Feature::PathDifference,
+        Feature::PlayerDistance,
+        Feature::OpponentDistance,
+        Feature::WallDifference,
+        Feature::PlayerWalls,
+        Feature::OpponentWalls,
+        Feature::Attacking,
+        Feature::Defensive,
+        Feature::NextRowProgress,
+        Feature::WallThreat,
+        Feature::WallOpportunity,
+        Feature::PathMultiplicity,
+        Feature::DisjointPaths,
+        Feature::ChokePoints,
+        Feature::RaceTempo,
+        Feature::WallParity,
+    ];
+    
+    pub fn name(&self) -> &'static str {
+        match self {
+            Feature::PathDifference => "path_difference",
+            Feature::PlayerDistance => "player_distance",
+            Feature::OpponentDistance => "opponent_distance",
+            Feature::WallDifference => "wall_difference",
+            Feature::PlayerWalls => "player_walls",
+            Feature::OpponentWalls => "opponent_walls",
+            Feature::Attacking => "attacking",
+            Feature::Defensive => "defensive",
+            Feature::NextRowProgress => "next_row_progress",
+            Feature::WallThreat => "wall_threat",
+            Feature::WallOpportunity => "wall_opportunity",
+            Feature::PathMultiplicity => "path_multiplicity",
+            Feature::DisjointPaths => "disjoint_paths",
+            Feature::ChokePoints => "choke_points",
+            Feature::RaceTempo => "race_tempo",
+            Feature::WallParity => "wall_parity",
+        }
+    }
+    
+    // The opponent is whoever is nearest their goal, so that every feature
+    // means something in a four-player game too
+    pub fn value(&self, game: &Quoridor, player: Player) -> f64 {
+        let opponent = game.leading_opponent(player);
+        match self {
+            Feature::PathDifference => game.distance_to_goal(opponent) as f64 - game.distance_to_goal(player) as f64,
+            Feature::PlayerDistance => game.distance_to_goal(player) as f64,
+            Feature::OpponentDistance => game.distance_to_goal(opponent) as f64,
+            Feature::WallDifference => game.walls_available[&player] as f64 - game.walls_available[&opponent] as f64,
+            Feature::PlayerWalls => game.walls_available[&player] as f64,
+            Feature::OpponentWalls => game.walls_available[&opponent] as f64,
+            Feature::Attacking => match game.moves_to_next_row(player) {
+                0 => 20.0, // Very high value for immediate progress
+                moves => 1.0 / moves as f64,
+            },
+            Feature::Defensive => game.moves_to_next_row(opponent) as f64,
+            Feature::NextRowProgress => 1.0 / (game.moves_to_next_row(player) as f64 + 0.1),
+            Feature::WallThreat if game.walls_available[&opponent] == 0 => 0.0,
+            Feature::WallThreat => game.best_wall_damage(player) as f64,
+            Feature::WallOpportunity if game.walls_available[&player] == 0 => 0.0,
+            Feature::WallOpportunity => game.best_wall_damage(opponent) as f64,
+            Feature::PathMultiplicity => (game.shortest_path_count(player).max(1) as f64).log2(),
+            Feature::DisjointPaths => game.disjoint_shortest_paths(player) as f64,
+            Feature::ChokePoints => game.choke_points(player).len() as f64,
+            Feature::RaceTempo => game.race_tempo(player) as f64,
+            Feature::WallParity => game.wall_parity(player) as f64,
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = QuoridorError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Feature::ALL.into_iter()
+            .find(|feature| feature.name() == s)
+            .ok_or_else(|| QuoridorError::UnknownEvaluator(s.to_string()))
+    }
+}
+
+// Strategy C3 from the paper: w2*f2 + w3*f3 + w4*f4 over the path
+// difference, attacking and defensive features
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct C3Evaluator {
+    pub weights: EvalWeights,
+}
+
+impl C3Evaluator {
+    pub const FEATURES: [Feature; 3] = [Feature::PathDifference, Feature::Attacking, Feature::Defensive];
+    
+    pub fn new(weights: EvalWeights) -> Self {
+        C3Evaluator { weights }
+    }
+    
+    // The features C3 weighs, in the order of `EvalWeights::to_array`
+    pub fn features(game: &Quoridor, player: Player) -> [f64; 3] {
+        Self::FEATURES.map(|feature| feature.value(game, player))
+    }
+}
+
+impl Default for C3Evaluator {
+    fn default() -> Self {
+        Self::new(EvalWeights::PAPER)
+    }
+}
+
+impl Evaluator for C3Evaluator {
+    fn name(&self) -> String {
+        "C3".to_string()
+    }
+    
+    fn evaluate(&self, game: &Quoridor, player: Player) -> f64 {
+        let [f2, f3, f4] = Self::features(game, player);
+        let weights = &self.weights;
+        
+        // Combined evaluation: w2*f2 + w3*f3 + w4*f4
+        weights.position_difference * f2 + 
+        weights.attacking * f3 + 
+        weights.defensive * f4
+    }
+}
+
+// How many steps the player is ahead in the race to the goal, and nothing else
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PathDifferenceEvaluator;
+
+impl Evaluator for PathDifferenceEvaluator {
+    fn name(&self) -> String {
+        "PathDifference".to_string()
+    }
+    
+    fn evaluate(&self, game: &Quoridor, player: Player) -> f64 {
+        Feature::PathDifference.value(game, player)
+    }
+}
+
+// Any weighted sum of features, named like "0.6*path_difference+6.52*wall_difference"
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedLinearEvaluator {
+    pub terms: Vec<(f64, Feature)>,
+}
+
+impl WeightedLinearEvaluator {
+    pub fn new(terms: Vec<(f64, Feature)>) -> Self {
+        WeightedLinearEvaluator { terms }
+    }
+}
+
+impl Evaluator for WeightedLinearEvaluator {
+    fn name(&self) -> String {
+        let terms: Vec<String> = self.terms.iter().map(|(weight, feature)| format!("{}*{}", weight, feature)).collect();
+        terms.join("+")
+    }
+    
+    fn evaluate(&self, game: &Quoridor, player: Player) -> f64 {
+        self.terms.iter().map(|(weight, feature)| weight * feature.value(game, player)).sum()
+    }
+}
+
+impl FromStr for WeightedLinearEvaluator {
+    type Err = QuoridorError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let terms = s.split('+').map(|term| {
+            let unknown = || QuoridorError::UnknownEvaluator(term.to_string());
+            let (weight, feature) = term.split_once('*').ok_or_else(unknown)?;
+            let weight = weight.parse::<f64>().ok().filter(|weight| weight.is_finite()).ok_or_else(unknown)?;
+            Ok((weight, feature.parse()?))
+        }).collect::<Result<_, _>>()?;
+        Ok(WeightedLinearEvaluator::new(terms))
+    }
+}
+
+// An evaluator from its name: "C3", "PathDifference", or a weighted sum
+pub fn parse_evaluator(name: &str) -> Result<Box<dyn Evaluator>, QuoridorError> {
+    match name {
+        "C3" => Ok(Box::new(C3Evaluator::default())),
+        "PathDifference" => Ok(Box::new(PathDifferenceEvaluator)),
+        _ => Ok(Box::new(name.parse::<WeightedLinearEvaluator>()?)),
+    }
+}
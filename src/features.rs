@@ -0,0 +1,281 @@
+// Features of a position beyond path lengths, for evaluators to weigh.
+//
+// Most of them look at the shortest paths from a pawn to its goal. Every
+// square on some shortest path lies a fixed number of steps from the pawn,
+// its level, and each shortest path visits one square of each level, so the
+// squares by level (see `shortest_path_levels`) show how many ways there are
+// to the goal, where they all squeeze through one square, and which walls
+// could lengthen them. None of these account for the other pawns, just as
+// `distance_to_goal` does not; `race_tempo` is the exception.
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use petgraph::graph::NodeIndex;
+
+use super::{Coord, GameResult, Orientation, Player, Quoridor};
+
+impl Quoridor {
+    // The squares on the player's shortest paths, by level: the pawn's
+    // square first and goal squares last. Empty for a pawn that is cut off.
+    fn shortest_path_levels(&self, player: Player) -> Vec<Vec<NodeIndex>> {
+        let pawn = self.node_indices[&self.pawn_positions[&player]];
+        self.with_distance_field(player, |field| {
+            if field[pawn.index()] == usize::MAX {
+                return Vec::new();
+            }
+            let mut levels = vec![vec![pawn]];
+            while let Some(&square) = levels.last().and_then(|level| level.first()).filter(|square| field[square.index()] > 0) {
+                let nearer = field[square.index()] - 1;
+                let mut next: Vec<NodeIndex> = levels[levels.len() - 1].iter()
+                    .flat_map(|&node| self.graph.neighbors(node))
+                    .filter(|next| field[next.index()] == nearer)
+                    .collect();
+                next.sort();
+                next.dedup();
+                levels.push(next);
+            }
+            levels
+        })
+    }
+    
+    // How many shortest paths the player has to the goal (saturating), 0
+    // if cut off
+    pub fn shortest_path_count(&self, player: Player) -> u64 {
+        let levels = self.shortest_path_levels(player);
+        let Some(first) = levels.first() else {
+            return 0;
+        };
+        
+        let mut ways: HashMap<NodeIndex, u64> = first.iter().map(|&node| (node, 1)).collect();
+        for level in &levels[1..] {
+            ways = level.iter().map(|&node| {
+                let count = self.graph.neighbors(node)
+                    .filter_map(|previous| ways.get(&previous))
+                    .fold(0u64, |total, &count| total.saturating_add(count));
+                (node, count)
+            }).collect();
+        }
+        ways.values().fold(0, |total, &count| total.saturating_add(count))
+    }
+    
+    // Most shortest paths the player has that share no step: how many walls
+    // across them it takes, at least, before the pawn has to go further
+    pub fn disjoint_shortest_paths(&self, player: Player) -> usize {
+        let levels = self.shortest_path_levels(player);
+        let Some(&pawn) = levels.first().and_then(|level| level.first()) else {
+            return 0;
+        };
+        let level_of: HashMap<NodeIndex, usize> = levels.iter().enumerate()
+            .flat_map(|(i, level)| level.iter().map(move |&node| (node, i)))
+            .collect();
+        let last = levels.len() - 1;
+        
+        // Unit-capacity max flow along the steps between levels, one
+        // augmenting path at a time
+        let mut used: HashSet<(NodeIndex, NodeIndex)> = HashSet::new();
+        let mut paths = 0;
+        loop {
+            let mut parent: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+            let mut queue = VecDeque::from([pawn]);
+            let mut end = None;
+            while let Some(node) = queue.pop_front() {
+                if level_of[&node] == last {
+                    end = Some(node);
+                    break;
+                }
+                for next in self.graph.neighbors(node) {
+                    let Some(&level) = level_of.get(&next) else {
+                        continue;
+                    };
+                    // A step onwards not yet taken, or one back along a path to reroute it
+                    let open = if level == level_of[&node] + 1 {
+                        !used.contains(&(node, next))
+                    } else {
+                        level + 1 == level_of[&node] && used.contains(&(next, node))
+                    };
+                    if open && next != pawn && !parent.contains_key(&next) {
+                        parent.insert(next, node);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            
+            let Some(mut node) = end else {
+                return paths;
+            };
+            while let Some(&previous) = parent.get(&node) {
+                if !used.remove(&(node, previous)) {
+                    used.insert((previous, node));
+                }
+                node = previous;
+            }
+            paths += 1;
+        }
+    }
+    
+    // Squares that every one of the player's shortest paths passes through,
+    // nearest first, not counting the pawn's own. A run of them is a
+    // corridor, which one wall at the right place could lengthen.
+    pub fn choke_points(&self, player: Player) -> Vec<Coord> {
+        self.shortest_path_levels(player).iter()
+            .skip(1)
+            .filter(|level| level.len() == 1)
+            .map(|level| self.graph[level[0]])
+            .collect()
+    }
+    
+    // The player's distance to the goal with the edges cut, None if that
+    // cuts them off. A* search out from the pawn, guided by the uncut
+    // distance field, which the cut can only lengthen: it looks at little
+    // more than the squares of the new shortest paths.
+    fn distance_with_cut(&self, player: Player, cut: &[(Coord, Coord)]) -> Option<usize> {
+        let pawn = self.node_indices[&self.pawn_positions[&player]];
+        self.with_distance_field(player, |field| {
+            let start = field[pawn.index()];
+            if start == usize::MAX {
+                return None;
+            }
+            // Squares by estimated length of a path through them, from the
+            // pawn's own distance up
+            let mut steps = vec![usize::MAX; field.len()];
+            let mut buckets: Vec<Vec<NodeIndex>> = vec![vec![pawn]];
+            steps[pawn.index()] = 0;
+            let mut estimate = 0;
+            while estimate < buckets.len() {
+                let Some(node) = buckets[estimate].pop() else {
+                    estimate += 1;
+                    continue;
+                };
+                let taken = steps[node.index()];
+                if taken + field[node.index()] != start + estimate {
+                    continue; // Reached more cheaply since
+                }
+                if field[node.index()] == 0 {
+                    return Some(taken);
+                }
+                let from = self.graph[node];
+                for next in self.graph.neighbors(node) {
+                    let to = self.graph[next];
+                    if field[next.index()] == usize::MAX || taken + 1 >= steps[next.index()]
+                        || cut.iter().any(|&edge| edge == (from, to) || edge == (to, from)) {
+                        continue;
+                    }
+                    steps[next.index()] = taken + 1;
+                    let bucket = taken + 1 + field[next.index()] - start;
+                    if buckets.len() <= bucket {
+                        buckets.resize(bucket + 1, Vec::new());
+                    }
+                    buckets[bucket].push(next);
+                }
+            }
+            None
+        })
+    }
+    
+    // Most that one wall could add to the player's distance to the goal,
+    // whoever placed it, among walls that fit and leave every pawn a path
+    pub fn best_wall_damage(&self, player: Player) -> usize {
+        let levels = self.shortest_path_levels(player);
+        if levels.is_empty() {
+            return 0;
+        }
+        let level_of: HashMap<Coord, usize> = levels.iter().enumerate()
+            .flat_map(|(i, level)| level.iter().map(move |&node| (self.graph[node], i)))
+            .collect();
+        let distance = levels.len() - 1;
+        // The search below covers the player's own path
+        let mut routes = self.shortest_routes();
+        routes.remove(&player);
+        
+        let mut best = 0;
+        for row in 0..self.size {
+            for col in 0..self.size {
+                for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+                    if !self.wall_on_board((row, col), orientation) || !self.wall_slots.fits((row, col), orientation) {
+                        continue;
+                    }
+                    let edges: Vec<_> = self.wall_edges((row, col), orientation).collect();
+                    
+                    // Only a wall across a step of a shortest path can lengthen it
+                    let crosses = edges.iter().any(|(from, to)| {
+                        matches!((level_of.get(from), level_of.get(to)), (Some(&a), Some(&b)) if a.abs_diff(b) == 1)
+                    });
+                    if !crosses {
+                        continue;
+                    }
+                    let Some(longer) = self.distance_with_cut(player, &edges) else {
+                        continue;
+                    };
+                    if longer > distance && !self.blocks_a_path(&edges, &routes) {
+                        best = best.max(longer - distance);
+                    }
+                }
+            }
+        }
+        best
+    }
+    
+    // Who wins if nobody places another wall and every pawn steps (or
+    // jumps) as near its goal as it can, by how far: the moves the nearest
+    // other pawn still needs when the player arrives, or minus the moves the
+    // player still needs when another pawn does. 0 if the race is drawn, by
+    // the move limit or by going round in circles. Played out on the pawns'
+    // squares alone, stepping down the cached distance fields.
+    pub fn race_tempo(&self, player: Player) -> isize {
+        let mut pawns: Vec<Coord> = self.players.iter().map(|p| self.pawn_positions[p]).collect();
+        let mut winner = match self.outcome() {
+            Some(GameResult::Win(winner)) => Some(winner),
+            Some(_) => return 0,
+            None => None,
+        };
+        let mut plies = self.history.len();
+        let mut seen = HashSet::new();
+        let mut turn = self.players.iter().position(|&p| p == self.active_player).expect("the active player is in the game");
+        while winner.is_none() {
+            let mover = self.players[turn];
+            let occupied: Vec<Coord> = pawns.iter().enumerate().filter(|&(i, _)| i != turn).map(|(_, &pos)| pos).collect();
+            let Some(to) = self.pawn_moves(pawns[turn], &occupied).into_iter()
+                .map(|mv| self.move_coord(mv))
+                .min_by_key(|&to| self.distance_from(mover, to)) else {
+                return 0;
+            };
+            pawns[turn] = to;
+            plies += 1;
+            turn = (turn + 1) % pawns.len();
+            if self.goal_positions[&mover].contains(&to) {
+                winner = Some(mover);
+            } else if plies >= self.rules.move_limit || !seen.insert((pawns.clone(), turn)) {
+                return 0;
+            }
+        }
+        
+        let distance = |other: Player| {
+            let index = self.players.iter().position(|&p| p == other).expect("every player is in the game");
+            self.distance_from(other, pawns[index])
+        };
+        match winner {
+            Some(winner) if winner == player => self.players.iter()
+                .filter(|&&other| other != player)
+                .map(|&other| distance(other))
+                .min()
+                .map_or(0, |distance| distance as isize),
+            _ => -(distance(player) as isize),
+        }
+    }
+    
+    // Whether the player would place the last wall if they and the leading
+    // opponent went on placing one each in turn, the side to move first, for
+    // as long as either has any: 1 if so, -1 if the opponent would, 0 with
+    // none left
+    pub fn wall_parity(&self, player: Player) -> isize {
+        let opponent = self.leading_opponent(player);
+        let (mine, theirs) = (self.walls_available[&player], self.walls_available[&opponent]);
+        if mine + theirs == 0 {
+            return 0;
+        }
+        
+        // The side to move has the last wall only with more walls than the other
+        let to_move = self.active_player == player;
+        let (moving, waiting) = if to_move { (mine, theirs) } else { (theirs, mine) };
+        if (moving > waiting) == to_move { 1 } else { -1 }
+    }
+}
@@ -1,6 +1,8 @@
 // This declares the main module to be available to lib.rs
 #[path = "main.rs"]
-mod main;
+pub mod main;
+
+use std::collections::HashMap;
 
 // Import wasm-bindgen
 use wasm_bindgen::prelude::*;
@@ -26,34 +28,33 @@ pub fn wasm_log(s: &str) {
 pub struct QuoridorGame {
     // We'll store the actual game instance here
     game_instance: main::Quoridor,
-    // And strategy instances
-    player1_strategy: Option<Box<dyn main::Strategy>>,
-    player2_strategy: Option<Box<dyn main::Strategy>>,
+    // And the strategy of each player the computer plays for
+    strategies: HashMap<main::Player, Box<dyn main::Strategy>>,
 }
 
 #[wasm_bindgen]
 impl QuoridorGame {
     #[wasm_bindgen(constructor)]
-    pub fn new(size: usize, walls: usize) -> Self {
+    pub fn new(size: usize, walls: usize) -> Result<QuoridorGame, JsValue> {
         console_error_panic_hook::set_once();
-        let game = main::Quoridor::new(size, walls, None);
-        Self {
+        let game = main::Quoridor::new(size, walls, main::RuleSet::default(), None)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(Self {
             game_instance: game,
-            player1_strategy: None,
-            player2_strategy: None,
-        }
+            strategies: HashMap::new(),
+        })
     }
 
-    // Method to set a strategy for a player
+    // Method to set a strategy for a player; false for an unknown strategy
+    // or a player number not in the game
     pub fn set_strategy(&mut self, player_number: usize, strategy_name: &str, opening_name: &str) -> bool {
-        let player = if player_number == 1 {
-            main::Player::Player1
-        } else {
-            main::Player::Player2
+        let player = match main::Player::from_number(player_number) {
+            Some(player) if self.game_instance.players.contains(&player) => player,
+            _ => return false,
         };
 
         // Get opening moves
-        let opening_moves = main::get_opening_moves(opening_name, player);
+        let opening_moves = main::get_opening_moves(opening_name, player, self.game_instance.size);
         
         // Create the appropriate strategy
         let strategy: Box<dyn main::Strategy> = match strategy_name {
@@ -69,7 +70,13 @@ impl QuoridorGame {
             },
             s if s.starts_with("Minimax") => {
                 let depth = s[7..].parse::<usize>().unwrap_or(1);
-                Box::new(main::MinimaxStrategy::new(opening_name, opening_moves, depth))
+                Box::new(main::MinimaxStrategy::new(opening_name, opening_moves, depth, main::DEFAULT_TABLE_MB))
+            },
+            s if s.starts_with("IDMinimax") => {
+                // Time budgets are turned into node budgets, as there is no clock to read
+                let limit = s[9..].parse::<main::SearchLimit>()
+                    .unwrap_or(main::SearchLimit::Time(std::time::Duration::from_secs(1)));
+                Box::new(main::MinimaxStrategy::iterative(opening_name, opening_moves, limit, main::DEFAULT_TABLE_MB))
             },
             s if s.starts_with("MCTS") => {
                 // For WebAssembly, we need to handle MCTS differently
@@ -97,57 +104,100 @@ impl QuoridorGame {
         };
         
         // Store the strategy
-        if player_number == 1 {
-            self.player1_strategy = Some(strategy);
-        } else {
-            self.player2_strategy = Some(strategy);
-        }
+        self.strategies.insert(player, strategy);
         
         true
     }
 
     // Get AI move for current player
     pub fn get_ai_move(&mut self) -> String {
+        if self.game_instance.outcome().is_some() {
+            return "".to_string();
+        }
         let active_player = self.game_instance.active_player;
         
-        let strategy = if active_player == main::Player::Player1 {
-            &mut self.player1_strategy
-        } else {
-            &mut self.player2_strategy
-        };
-        
-        if let Some(strategy) = strategy {
-            if let Some(move_str) = strategy.choose_move(&self.game_instance) {
-                return move_str;
+        if let Some(strategy) = self.strategies.get_mut(&active_player) {
+            if let Some(mv) = strategy.choose_move(&self.game_instance) {
+                return mv.to_string();
             }
         }
         
         "".to_string()
     }
     
+    // What the player's last search found, as JSON, or an empty string if
+    // their strategy does not search or has not searched yet
+    pub fn get_search_info(&self, player_number: usize) -> String {
+        let strategy = main::Player::from_number(player_number).and_then(|player| self.strategies.get(&player));
+        let Some(info) = strategy.and_then(|strategy| strategy.search_info()) else {
+            return "".to_string();
+        };
+        
+        let pv: Vec<String> = info.pv.iter().map(|mv| mv.to_string()).collect();
+        format!(
+            r#"{{
+                "bestMove": {:?},
+                "score": {:.3},
+                "pv": {:?},
+                "depth": {},
+                "nodes": {},
+                "timeMs": {},
+                "tableHits": {}
+            }}"#,
+            info.best_move.map(|mv| mv.to_string()).unwrap_or_default(),
+            info.score,
+            pv,
+            info.depth,
+            info.nodes,
+            info.time.as_millis(),
+            info.table_hits
+        )
+    }
+    
     // Make a move (pawn or wall)
     pub fn make_move(&mut self, move_str: &str) -> bool {
-        if move_str.len() >= 3 && (move_str.ends_with('h') || move_str.ends_with('v')) {
-            self.game_instance.add_wall(move_str, false, true)
-        } else {
-            self.game_instance.move_pawn(move_str, true)
-        }
+        self.try_move(move_str).is_ok()
+    }
+    
+    // Make a move, throwing a message explaining why it was rejected
+    pub fn try_move(&mut self, move_str: &str) -> Result<(), JsValue> {
+        move_str.parse::<main::Move>()
+            .and_then(|mv| self.game_instance.apply(mv))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
     }
     
     // Get legal pawn moves
     pub fn get_legal_moves(&self) -> Vec<String> {
         self.game_instance.get_legal_moves(self.game_instance.active_player)
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect()
     }
     
     // Get legal wall placements
     pub fn get_legal_walls(&self) -> Vec<String> {
         self.game_instance.get_legal_walls(self.game_instance.active_player)
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect()
     }
     
     // Get current game state as JSON
     pub fn get_game_state(&self) -> String {
-        let p1 = self.game_instance.pawn_positions.get(&main::Player::Player1).unwrap();
-        let p2 = self.game_instance.pawn_positions.get(&main::Player::Player2).unwrap();
+        let size = self.game_instance.size;
+        
+        // Each player's pawn and walls left, keyed by name ("player3", "player3Walls")
+        let mut numbered = self.game_instance.players.clone();
+        numbered.sort_by_key(|player| player.index());
+        let players: Vec<String> = numbered.iter()
+            .map(|player| {
+                let (row, col) = self.game_instance.pawn_positions[player];
+                format!(
+                    r#""{name}": {{ "row": {}, "col": {} }}, "{name}Walls": {}"#,
+                    row, col, self.game_instance.walls_available[player], name = player.name()
+                )
+            })
+            .collect();
         
         let h_walls: Vec<String> = self.game_instance.hwall_positions.iter()
             .map(|&pos| self.game_instance.coord_to_algebraic(pos))
@@ -159,31 +209,68 @@ impl QuoridorGame {
         
         format!(
             r#"{{
-                "player1": {{ "row": {}, "col": {} }},
-                "player2": {{ "row": {}, "col": {} }},
-                "player1Walls": {},
-                "player2Walls": {},
+                "size": {},
+                "playerCount": {},
+                {},
                 "hWalls": {:?},
                 "vWalls": {:?},
-                "activePlayer": {}
+                "activePlayer": {},
+                "winner": {},
+                "result": {:?}
             }}"#,
-            p1.0, p1.1,
-            p2.0, p2.1,
-            self.game_instance.walls_available[&main::Player::Player1],
-            self.game_instance.walls_available[&main::Player::Player2],
+            size,
+            numbered.len(),
+            players.join(",\n                "),
             h_walls, v_walls,
-            if self.game_instance.active_player == main::Player::Player1 { 1 } else { 2 }
+            self.game_instance.active_player.index() + 1,
+            self.get_winner(),
+            self.get_result()
         )
     }
     
     // Check if a move is a winning move
     pub fn check_win(&self, move_str: &str) -> bool {
-        self.game_instance.win_check(move_str)
+        match move_str.parse::<main::Move>() {
+            Ok(mv) => self.game_instance.win_check(mv),
+            Err(_) => false,
+        }
+    }
+    
+    // How the game ended ("player1 wins", "draw by repetition", ...), or
+    // an empty string while it is still being played
+    pub fn get_result(&self) -> String {
+        self.game_instance.outcome()
+            .map(|result| result.to_string())
+            .unwrap_or_default()
     }
     
-    // Get active player (1 or 2)
+    // Winning player's number, or 0 if nobody has won
+    pub fn get_winner(&self) -> usize {
+        self.game_instance.outcome()
+            .and_then(|result| result.winner())
+            .map_or(0, |player| player.index() + 1)
+    }
+    
+    // Get active player's number (1 to 4)
     pub fn get_active_player(&self) -> usize {
-        if self.game_instance.active_player == main::Player::Player1 { 1 } else { 2 }
+        self.game_instance.active_player.index() + 1
+    }
+    
+    // Take back the last move; false if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        self.game_instance.undo().is_some()
+    }
+    
+    // Replay the last undone move; false if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        self.game_instance.redo().is_some()
+    }
+    
+    // Moves played so far, in algebraic notation
+    pub fn get_move_history(&self) -> Vec<String> {
+        self.game_instance.history.iter()
+            .map(|record| record.mv.to_string())
+            .collect()
     }
     
     // Reset the game
@@ -191,7 +278,8 @@ impl QuoridorGame {
         self.game_instance = main::Quoridor::new(
             self.game_instance.size,
             self.game_instance.walls,
+            self.game_instance.rules,
             None
-        );
+        ).expect("current board configuration is valid");
     }
 }
\ No newline at end of file
@@ -1,22 +1,57 @@
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::fs::File;
-use std::io::Write;
+use std::fmt;
 use std::path::Path;
+use std::str::FromStr;
 use rand::prelude::*;
 use csv::Writer;
-use petgraph::graph::{Graph, NodeIndex, UnGraph};
-use petgraph::algo::{dijkstra, has_path_connecting};
-use std::cmp::{min, max};
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::algo::has_path_connecting;
 use std::env;
+use endgame::PawnRaceCache;
+use evaluation::{C3Evaluator, Evaluator, Feature, PathDifferenceEvaluator, WeightedLinearEvaluator};
+use ordering::MoveOrdering;
+use solver::SolutionTable;
+use transposition::{Bound, TranspositionTable};
 use std::thread;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration};
 
-// Define coordinate type for clarity
-type Coord = (usize, usize);
+pub mod bitboard;
+pub mod endgame;
+pub mod evaluation;
+pub mod features;
+pub mod ordering;
+pub mod solver;
+pub mod transposition;
+pub mod tuning;
+pub mod zobrist;
+
+// Define coordinate type for clarity: (row, col), where row 0 is the top
+// rank (player 2's edge) and row size - 1 is rank 1
+pub type Coord = (usize, usize);
+
+// Board sizes the engine supports; the largest uses files a to s
+pub const MIN_BOARD_SIZE: usize = 5;
+pub const MAX_BOARD_SIZE: usize = 19;
+
+// Moves, counting each player's, after which a game is drawn
+pub const DEFAULT_MOVE_LIMIT: usize = 150;
+
+// Most walls each player may start with. A wall covers `wall_length` of the
+// 2 * size * (size - 1) groove segments, so with every player's walls on the
+// board this is as many as could ever fit.
+pub fn max_walls(size: usize, rules: RuleSet) -> usize {
+    2 * size * (size - 1) / rules.wall_length / rules.player_count
+}
+
+// The standard 10 walls each, scaled by board area (3 on 5x5, 16 on 11x11)
+pub fn default_walls(size: usize) -> usize {
+    (size.saturating_sub(1).pow(2) * 10 + 32) / 64
+}
 
+use std::time::Duration;
 #[cfg(not(target_arch = "wasm32"))]
-use std::time::{Instant};
+use std::time::Instant;
 
 #[cfg(target_arch = "wasm32")]
 struct WasmSafeInstant {
@@ -35,18 +70,25 @@ impl WasmSafeInstant {
     }
 }
 
-// Enum for player identification
+// Enum for player identification. Each player has a fixed seat: player 1
+// starts at the bottom edge, player 2 at the top, and in four-player games
+// player 3 at the left edge and player 4 at the right.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Player {
     Player1,
     Player2,
+    Player3,
+    Player4,
 }
 
 impl Player {
+    // The player seated across the board: the opponent in a two-player game
     pub fn opponent(&self) -> Self {
         match self {
             Player::Player1 => Player::Player2,
             Player::Player2 => Player::Player1,
+            Player::Player3 => Player::Player4,
+            Player::Player4 => Player::Player3,
         }
     }
 
@@ -54,15 +96,419 @@ impl Player {
         match self {
             Player::Player1 => "player1",
             Player::Player2 => "player2",
+            Player::Player3 => "player3",
+            Player::Player4 => "player4",
+        }
+    }
+
+    // Position in per-player arrays
+    pub fn index(&self) -> usize {
+        match self {
+            Player::Player1 => 0,
+            Player::Player2 => 1,
+            Player::Player3 => 2,
+            Player::Player4 => 3,
+        }
+    }
+    
+    // Player by number, as written in state strings (1 to 4)
+    pub fn from_number(number: usize) -> Option<Self> {
+        match number {
+            1 => Some(Player::Player1),
+            2 => Some(Player::Player2),
+            3 => Some(Player::Player3),
+            4 => Some(Player::Player4),
+            _ => None,
+        }
+    }
+}
+
+// Wall orientation, written as the trailing 'h' or 'v' of a wall move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+impl Orientation {
+    pub fn as_char(&self) -> char {
+        match self {
+            Orientation::Horizontal => 'h',
+            Orientation::Vertical => 'v',
+        }
+    }
+}
+
+// A square by its algebraic name: file 0 is 'a', and rank 1 is player 1's
+// edge. Unlike a `Coord`, the name does not depend on the board size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Square {
+    pub file: usize,
+    pub rank: usize,
+}
+
+impl Square {
+    pub fn new(file: usize, rank: usize) -> Self {
+        Square { file, rank }
+    }
+    
+    // The square at a board coordinate
+    pub fn at(size: usize, (row, col): Coord) -> Self {
+        Square { file: col, rank: size.saturating_sub(row) }
+    }
+    
+    // Where the square is on a board of the given size, if it is on it
+    pub fn coord(&self, size: usize) -> Option<Coord> {
+        (self.file < size && (1..=size).contains(&self.rank)).then(|| (size - self.rank, self.file))
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file as u8) as char, self.rank)
+    }
+}
+
+// A single move: either a pawn step to a square, or a wall anchored at the
+// bottom-left square of the 2x2 block it separates.
+//
+// Moves display as algebraic notation ("e2", "d3h") and parse back from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Pawn(Square),
+    Wall(Square, Orientation),
+}
+
+impl Move {
+    pub fn is_wall(&self) -> bool {
+        matches!(self, Move::Wall(..))
+    }
+
+    pub fn square(&self) -> Square {
+        match *self {
+            Move::Pawn(square) | Move::Wall(square, _) => square,
         }
     }
 }
 
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.square())?;
+        if let Move::Wall(_, orientation) = self {
+            write!(f, "{}", orientation.as_char())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Move {
+    type Err = QuoridorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (square, orientation) = match s.as_bytes().last() {
+            Some(b'h') => (&s[..s.len() - 1], Some(Orientation::Horizontal)),
+            Some(b'v') => (&s[..s.len() - 1], Some(Orientation::Vertical)),
+            _ => (s, None),
+        };
+
+        let mut chars = square.chars();
+        let file = match chars.next() {
+            Some(c) if c.is_ascii_alphabetic() => (c.to_ascii_lowercase() as u8 - b'a') as usize,
+            _ => return Err(QuoridorError::BadNotation(s.to_string())),
+        };
+
+        let rank_digits = chars.as_str();
+        if rank_digits.is_empty() || !rank_digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(QuoridorError::BadNotation(s.to_string()));
+        }
+        let rank = match rank_digits.parse::<usize>() {
+            Ok(rank) if rank >= 1 => rank,
+            _ => return Err(QuoridorError::BadNotation(s.to_string())),
+        };
+
+        let square = Square::new(file, rank);
+        Ok(match orientation {
+            Some(orientation) => Move::Wall(square, orientation),
+            None => Move::Pawn(square),
+        })
+    }
+}
+
+// Reasons a move, square or state string can be rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoridorError {
+    BadNotation(String),    // Not algebraic notation at all, e.g. "5e" or "e0"
+    OutOfBounds(Move),      // Well-formed, but off the board (or a wall hanging off its edge)
+    IllegalMove(Move),      // Pawn move the rules do not allow from this position
+    WallOverlap(Move),      // Wall overlaps or crosses a wall already on the board
+    PathBlocked(Move),      // Wall would cut a player off from their goal
+    NoWallsLeft(Player),    // Player has already placed all of their walls
+    MalformedState(String), // State string that cannot be parsed
+    UnsupportedBoardSize(usize), // Board size a representation cannot hold
+    TooManyWalls(usize, usize),  // More walls per player than the board holds: (walls, size)
+    UnsupportedPlayerCount(usize), // Games are for two or four players
+    UnsupportedRules(String),      // Rule set that cannot be played, or not by this code
+    GameOver(GameResult),          // No more moves once the game has a result
+    MalformedWeights(String),      // Evaluation weights that cannot be parsed
+    UnknownEvaluator(String),      // Evaluator name (or one of its terms) that is not known
+    NoSolution,                    // The Perfect strategy without a solved table to play from
+    SolutionTooLong(usize),        // Best play runs past the moves a solution table holds: (the most it holds)
+}
+
+impl fmt::Display for QuoridorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoridorError::BadNotation(s) => write!(f, "invalid algebraic notation: {}", s),
+            QuoridorError::OutOfBounds(mv) => write!(f, "{} is outside the board", mv),
+            QuoridorError::IllegalMove(mv) => write!(f, "{} is not a legal pawn move", mv),
+            QuoridorError::WallOverlap(mv) => write!(f, "wall {} overlaps an existing wall", mv),
+            QuoridorError::PathBlocked(mv) => write!(f, "wall {} would block a player from their goal", mv),
+            QuoridorError::NoWallsLeft(player) => write!(f, "{} has no walls left", player.name()),
+            QuoridorError::MalformedState(reason) => write!(f, "malformed state string: {}", reason),
+            QuoridorError::UnsupportedBoardSize(size) => write!(f, "board size {} is not supported", size),
+            QuoridorError::TooManyWalls(walls, size) => write!(f, "{} walls per player do not fit on a {}x{} board", walls, size, size),
+            QuoridorError::UnsupportedPlayerCount(count) => write!(f, "{}-player games are not supported", count),
+            QuoridorError::UnsupportedRules(reason) => write!(f, "unsupported rules: {}", reason),
+            QuoridorError::GameOver(result) => write!(f, "the game is over: {}", result),
+            QuoridorError::MalformedWeights(reason) => write!(f, "malformed evaluation weights: {}", reason),
+            QuoridorError::UnknownEvaluator(name) => write!(f, "unknown evaluator: {}", name),
+            QuoridorError::NoSolution => write!(f, "the Perfect strategy needs a solved table and none is loaded"),
+            QuoridorError::SolutionTooLong(plies) => write!(f, "best play runs past the {} moves a solution table holds", plies),
+        }
+    }
+}
+
+impl std::error::Error for QuoridorError {}
+
+// Where a player has to get to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GoalRule {
+    Edge,   // Any square on the opposite edge
+    Square, // Only the square straight across from the standard starting square
+}
+
+// Where pawns start on their own edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StartRule {
+    Centre,       // The middle of the edge
+    RandomColumn, // Anywhere along the edge, chosen when the game is set up
+}
+
+// The rules a game is played under. `RuleSet::default()` is standard
+// Quoridor; variants are written as '+'-separated names, e.g.
+// "long-walls+shared-walls", for the tournament and command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuleSet {
+    pub player_count: usize,    // 2, or 4 for the four-player variant
+    pub wall_length: usize,     // Squares each wall runs along
+    pub goal: GoalRule,
+    pub start: StartRule,
+    pub shared_wall_pool: bool, // Every wall placed comes out of one pool for all players
+    pub move_limit: usize,      // Moves, counting each player's, before the game is drawn
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            player_count: 2,
+            wall_length: 2,
+            goal: GoalRule::Edge,
+            start: StartRule::Centre,
+            shared_wall_pool: false,
+            move_limit: DEFAULT_MOVE_LIMIT,
+        }
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut variants = Vec::new();
+        if self.player_count == 4 {
+            variants.push("four-player");
+        }
+        if self.wall_length == 3 {
+            variants.push("long-walls");
+        }
+        if self.goal == GoalRule::Square {
+            variants.push("single-goal");
+        }
+        if self.start == StartRule::RandomColumn {
+            variants.push("random-start");
+        }
+        if self.shared_wall_pool {
+            variants.push("shared-walls");
+        }
+        let move_limit = format!("move-limit-{}", self.move_limit);
+        if self.move_limit != DEFAULT_MOVE_LIMIT {
+            variants.push(&move_limit);
+        }
+        if variants.is_empty() {
+            variants.push("standard");
+        }
+        
+        let names = variants.join("+");
+        if names.parse() == Ok(*self) {
+            write!(f, "{}", names)
+        } else {
+            // Parameters with no name of their own
+            write!(f, "{:?}", self)
+        }
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = QuoridorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = RuleSet::default();
+        for variant in s.split('+').map(str::trim) {
+            match variant {
+                "standard" => {},
+                "four-player" => rules.player_count = 4,
+                "long-walls" => rules.wall_length = 3,
+                "single-goal" => rules.goal = GoalRule::Square,
+                "random-start" => rules.start = StartRule::RandomColumn,
+                "shared-walls" => rules.shared_wall_pool = true,
+                _ if variant.starts_with("move-limit-") => {
+                    rules.move_limit = variant["move-limit-".len()..].parse()
+                        .map_err(|_| QuoridorError::UnsupportedRules(format!("bad move limit in '{}'", variant)))?;
+                },
+                _ => return Err(QuoridorError::UnsupportedRules(format!("unknown variant '{}'", variant))),
+            }
+        }
+        Ok(rules)
+    }
+}
+
+// Which parts of the wall grooves are taken. A wall runs along one segment
+// half per square it borders, and passes through the posts between them,
+// where a crossing wall would also have to pass; a standard two-square wall
+// has a single post at its centre. A wall fits only if its posts and halves
+// are all free, so walls may meet end to end or in a T but never overlap or
+// cross.
+#[derive(Debug, Clone)]
+pub struct WallSlots {
+    size: usize,
+    wall_length: usize,
+    posts: Vec<Option<Orientation>>, // Grid point above and right of (row, col), at (row - 1) * (size - 1) + col
+    hsegments: Vec<bool>,            // Groove above (row, col), at (row - 1) * size + col
+    vsegments: Vec<bool>,            // Groove right of (row, col), at col * size + row
+}
+
+impl WallSlots {
+    pub fn new(size: usize, wall_length: usize) -> Self {
+        let grooves = size.saturating_sub(1);
+        WallSlots {
+            size,
+            wall_length,
+            posts: vec![None; grooves * grooves],
+            hsegments: vec![false; grooves * size],
+            vsegments: vec![false; grooves * size],
+        }
+    }
+    
+    // The i-th post a wall anchored on the board passes through
+    fn post(&self, (row, col): Coord, orientation: Orientation, i: usize) -> usize {
+        match orientation {
+            Orientation::Horizontal => (row - 1) * (self.size - 1) + col + i,
+            Orientation::Vertical => (row - 1 - i) * (self.size - 1) + col,
+        }
+    }
+    
+    // The i-th segment half a wall anchored on the board runs along
+    fn half(&self, (row, col): Coord, orientation: Orientation, i: usize) -> usize {
+        match orientation {
+            Orientation::Horizontal => (row - 1) * self.size + col + i,
+            Orientation::Vertical => col * self.size + row - i,
+        }
+    }
+    
+    fn segments(&mut self, orientation: Orientation) -> &mut Vec<bool> {
+        match orientation {
+            Orientation::Horizontal => &mut self.hsegments,
+            Orientation::Vertical => &mut self.vsegments,
+        }
+    }
+    
+    // Whether a wall can go here without overlapping or crossing one already placed
+    pub fn fits(&self, coord: Coord, orientation: Orientation) -> bool {
+        let segments = match orientation {
+            Orientation::Horizontal => &self.hsegments,
+            Orientation::Vertical => &self.vsegments,
+        };
+        (0..self.wall_length - 1).all(|i| self.posts[self.post(coord, orientation, i)].is_none())
+            && (0..self.wall_length).all(|i| !segments[self.half(coord, orientation, i)])
+    }
+    
+    pub fn place(&mut self, coord: Coord, orientation: Orientation) {
+        self.mark(coord, orientation, true);
+    }
+    
+    // Free the slots of a wall placed with `place`
+    pub fn remove(&mut self, coord: Coord, orientation: Orientation) {
+        self.mark(coord, orientation, false);
+    }
+    
+    fn mark(&mut self, coord: Coord, orientation: Orientation, placed: bool) {
+        for i in 0..self.wall_length - 1 {
+            let post = self.post(coord, orientation, i);
+            self.posts[post] = placed.then_some(orientation);
+        }
+        for i in 0..self.wall_length {
+            let half = self.half(coord, orientation, i);
+            self.segments(orientation)[half] = placed;
+        }
+    }
+}
+
+// Why a game ended without a winner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    MoveLimit,
+    Repetition,
+}
+
+// How a finished game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    Win(Player),
+    Draw(DrawReason),
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameResult::Win(player) => Some(*player),
+            GameResult::Draw(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameResult::Win(player) => write!(f, "{} wins", player.name()),
+            GameResult::Draw(DrawReason::MoveLimit) => write!(f, "draw by move limit"),
+            GameResult::Draw(DrawReason::Repetition) => write!(f, "draw by repetition"),
+        }
+    }
+}
+
+// A move as it was played, with what is needed to take it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveRecord {
+    pub mv: Move,
+    pub player: Player,
+    pub previous_position: Coord,             // Mover's pawn square before the move
+    pub previous_outcome: Option<GameResult>, // Result before the move, normally none
+}
+
 // Game state representation
 #[derive(Clone)]
 pub struct Quoridor {
     pub size: usize,
     pub walls: usize,
+    pub rules: RuleSet,
     pub graph: UnGraph<Coord, ()>,
     pub node_indices: HashMap<Coord, NodeIndex>,
     pub hwall_positions: Vec<Coord>,
@@ -70,17 +516,46 @@ pub struct Quoridor {
     pub pawn_positions: HashMap<Player, Coord>,
     pub walls_available: HashMap<Player, usize>,
     pub active_player: Player,
+    pub players: Vec<Player>, // Everyone in the game, in turn order
     pub goal_positions: HashMap<Player, Vec<Coord>>,
     pub state_string: String,
-    pub previous_state: String,
-    pub last_move: String,
+    pub last_move: Option<Move>,
+    outcome: Option<GameResult>,
+    // `position_key` of the starting position and after each move in `history`
+    position_keys: Vec<u64>,
+    pub history: Vec<MoveRecord>,
+    pub redo_stack: Vec<MoveRecord>,
+    // Per player, BFS distance from every square to that player's goal row
+    // (indexed by node index). An entry is dropped when a wall change could
+    // alter it and rebuilt on the next query.
+    distance_fields: RefCell<HashMap<Player, Vec<usize>>>,
+    pub wall_slots: WallSlots,
 }
 
 impl Quoridor {
-    pub fn new(size: usize, walls: usize, state_string: Option<&str>) -> Self {
+    // A game under the given rules; `walls` is each player's allowance. Two-
+    // or four-player, with turns going clockwise from player 1: bottom, left,
+    // top, right.
+    pub fn new(size: usize, walls: usize, rules: RuleSet, state_string: Option<&str>) -> Result<Self, QuoridorError> {
+        let players = match rules.player_count {
+            2 => vec![Player::Player1, Player::Player2],
+            4 => vec![Player::Player1, Player::Player3, Player::Player2, Player::Player4],
+            count => return Err(QuoridorError::UnsupportedPlayerCount(count)),
+        };
+        if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+            return Err(QuoridorError::UnsupportedBoardSize(size));
+        }
+        if !(2..size).contains(&rules.wall_length) {
+            return Err(QuoridorError::UnsupportedRules(format!("{}-square walls on a {}x{} board", rules.wall_length, size, size)));
+        }
+        if walls > max_walls(size, rules) {
+            return Err(QuoridorError::TooManyWalls(walls, size));
+        }
+        
         let mut game = Quoridor {
             size,
             walls,
+            rules,
             graph: UnGraph::new_undirected(),
             node_indices: HashMap::new(),
             hwall_positions: Vec::new(),
@@ -88,42 +563,94 @@ impl Quoridor {
             pawn_positions: HashMap::new(),
             walls_available: HashMap::new(),
             active_player: Player::Player1,
+            players,
             goal_positions: HashMap::new(),
             state_string: String::new(),
-            previous_state: String::new(),
-            last_move: "Blank".to_string(),
+            last_move: None,
+            outcome: None,
+            position_keys: Vec::new(),
+            history: Vec::new(),
+            redo_stack: Vec::new(),
+            distance_fields: RefCell::new(HashMap::new()),
+            wall_slots: WallSlots::new(size, rules.wall_length),
         };
         
         // Initialize the graph
         game.initialize_graph();
         
-        // Set up goal positions
-        game.goal_positions.insert(
-            Player::Player1, 
-            (0..size).map(|i| (0, i)).collect()
-        );
-        game.goal_positions.insert(
-            Player::Player2, 
-            (0..size).map(|i| (size-1, i)).collect()
-        );
+        // Set up goal positions: each player heads for the edge opposite their
+        // own, or just the square across from where they would normally start
+        for player in game.players.clone() {
+            let goals = match (rules.goal, player) {
+                (GoalRule::Edge, Player::Player1) => (0..size).map(|i| (0, i)).collect(),
+                (GoalRule::Edge, Player::Player2) => (0..size).map(|i| (size - 1, i)).collect(),
+                (GoalRule::Edge, Player::Player3) => (0..size).map(|i| (i, size - 1)).collect(),
+                (GoalRule::Edge, Player::Player4) => (0..size).map(|i| (i, 0)).collect(),
+                (GoalRule::Square, _) => {
+                    let (row, col) = Self::centre_start(size, player);
+                    match player {
+                        Player::Player1 | Player::Player2 => vec![(size - 1 - row, col)],
+                        Player::Player3 | Player::Player4 => vec![(row, size - 1 - col)],
+                    }
+                },
+            };
+            game.goal_positions.insert(player, goals);
+        }
         
         // Parse state string or use default setup
         match state_string {
-            Some(state_str) => game.parse_state_string(state_str),
+            Some(state_str) => game.parse_state_string(state_str)?,
             None => {
-                // Default setup
-                let player1_start = (size - 1, (size - 1) / 2);
-                let player2_start = (0, size / 2);
-                game.pawn_positions.insert(Player::Player1, player1_start);
-                game.pawn_positions.insert(Player::Player2, player2_start);
-                game.walls_available.insert(Player::Player1, walls);
-                game.walls_available.insert(Player::Player2, walls);
+                // With a shared pool every player's count is the pool
+                let allowance = if rules.shared_wall_pool { walls * rules.player_count } else { walls };
+                for player in game.players.clone() {
+                    game.walls_available.insert(player, allowance);
+                }
+                
+                match rules.start {
+                    StartRule::Centre => {
+                        for player in game.players.clone() {
+                            game.pawn_positions.insert(player, Self::centre_start(size, player));
+                        }
+                    },
+                    StartRule::RandomColumn => {
+                        // Neighbouring edges share a corner, so draw again on a clash
+                        let mut rng = thread_rng();
+                        for player in game.players.clone() {
+                            let start = loop {
+                                let along = rng.gen_range(0..size);
+                                let start = match player {
+                                    Player::Player1 => (size - 1, along),
+                                    Player::Player2 => (0, along),
+                                    Player::Player3 => (along, 0),
+                                    Player::Player4 => (along, size - 1),
+                                };
+                                if !game.pawn_positions.values().any(|&taken| taken == start) {
+                                    break start;
+                                }
+                            };
+                            game.pawn_positions.insert(player, start);
+                        }
+                    },
+                }
                 game.active_player = Player::Player1;
                 game.update_state_string(true);
             }
         }
+        game.position_keys.push(game.full_position_key());
         
-        game
+        Ok(game)
+    }
+    
+    // Standard starting square: the middle of the player's edge, with each
+    // pair across the board placed symmetrically
+    fn centre_start(size: usize, player: Player) -> Coord {
+        match player {
+            Player::Player1 => (size - 1, (size - 1) / 2),
+            Player::Player2 => (0, size / 2),
+            Player::Player3 => ((size - 1) / 2, 0),
+            Player::Player4 => (size / 2, size - 1),
+        }
     }
     
     pub fn initialize_graph(&mut self) {
@@ -159,134 +686,153 @@ impl Quoridor {
         }
     }
 
-    pub fn parse_state_string(&mut self, state_string: &str) {
+    pub fn parse_state_string(&mut self, state_string: &str) -> Result<(), QuoridorError> {
+        let malformed = |reason: &str| QuoridorError::MalformedState(format!("{} in '{}'", reason, state_string));
+        
         let parts: Vec<&str> = state_string.split('/').collect();
         if parts.len() != 5 {
-            panic!("Invalid state string format");
+            return Err(malformed("expected 5 '/'-separated fields"));
         }
         
+        // Pawns and wall counts are listed by player number
+        let numbered = self.numbered_players();
+        
         // Parse pawn positions
-        let pawn_parts: Vec<&str> = parts[2].trim().split_whitespace().collect();
-        if pawn_parts.len() == 2 {
-            self.pawn_positions.insert(Player::Player1, self.algebraic_to_coord(pawn_parts[0]));
-            self.pawn_positions.insert(Player::Player2, self.algebraic_to_coord(pawn_parts[1]));
+        let pawn_parts: Vec<&str> = parts[2].split_whitespace().collect();
+        if pawn_parts.len() != numbered.len() {
+            return Err(malformed(&format!("expected {} pawn squares", numbered.len())));
+        }
+        let squares: Vec<Coord> = pawn_parts.iter()
+            .map(|square| self.algebraic_to_coord(square))
+            .collect::<Result<_, _>>()?;
+        if let Some(i) = (1..squares.len()).find(|&i| squares[..i].contains(&squares[i])) {
+            return Err(malformed(&format!("two pawns on {}", pawn_parts[i])));
+        }
+        for (&player, coord) in numbered.iter().zip(squares) {
+            self.pawn_positions.insert(player, coord);
         }
         
         // Parse walls available
-        let wall_parts: Vec<&str> = parts[3].trim().split_whitespace().collect();
-        if wall_parts.len() == 2 {
-            self.walls_available.insert(Player::Player1, wall_parts[0].parse().unwrap_or(self.walls));
-            self.walls_available.insert(Player::Player2, wall_parts[1].parse().unwrap_or(self.walls));
+        let wall_parts: Vec<usize> = parts[3].split_whitespace()
+            .map(|count| count.parse().map_err(|_| malformed("invalid wall count")))
+            .collect::<Result<_, _>>()?;
+        if wall_parts.len() != numbered.len() {
+            return Err(malformed(&format!("expected {} wall counts", numbered.len())));
+        }
+        if self.rules.shared_wall_pool && wall_parts.windows(2).any(|pair| pair[0] != pair[1]) {
+            return Err(malformed("wall counts differ with a shared pool"));
+        }
+        // A shared pool holds everyone's walls
+        let pooled = if self.rules.shared_wall_pool { self.rules.player_count } else { 1 };
+        if wall_parts.iter().any(|&count| count > max_walls(self.size, self.rules) * pooled) {
+            return Err(malformed("more walls in hand than fit on the board"));
+        }
+        for (&player, count) in numbered.iter().zip(wall_parts) {
+            self.walls_available.insert(player, count);
         }
         
         // Parse active player
-        let player_part = parts[4].trim();
-        self.active_player = if player_part == "1" { Player::Player1 } else { Player::Player2 };
-        
-        // Parse horizontal walls
-        let hwall_str = parts[0].trim();
-        if !hwall_str.is_empty() {
-            for i in (0..hwall_str.len()).step_by(2) {
-                if i + 2 <= hwall_str.len() {
-                    let wall = &hwall_str[i..i+2];
-                    let wall_move = format!("{}h", wall);
-                    self.add_wall(&wall_move, true, false);
-                }
-            }
-        }
+        self.active_player = match parts[4].trim().parse().ok().and_then(Player::from_number) {
+            Some(player) if self.players.contains(&player) => player,
+            _ => return Err(malformed("active player is not in the game")),
+        };
         
-        // Parse vertical walls
-        let vwall_str = parts[1].trim();
-        if !vwall_str.is_empty() {
-            for i in (0..vwall_str.len()).step_by(2) {
-                if i + 2 <= vwall_str.len() {
-                    let wall = &vwall_str[i..i+2];
-                    let wall_move = format!("{}v", wall);
-                    self.add_wall(&wall_move, true, false);
-                }
+        // Parse horizontal and vertical walls
+        // Squares are run together, each a file letter followed by its rank
+        for (wall_part, orientation) in [(parts[0], Orientation::Horizontal), (parts[1], Orientation::Vertical)] {
+            let wall_str = wall_part.trim();
+            let starts: Vec<usize> = wall_str.char_indices()
+                .filter(|(_, c)| c.is_ascii_lowercase())
+                .map(|(i, _)| i)
+                .collect();
+            if !wall_str.is_ascii() || (!wall_str.is_empty() && starts.first() != Some(&0)) {
+                return Err(malformed("wall list must be a run of squares"));
+            }
+            for (i, &start) in starts.iter().enumerate() {
+                let end = starts.get(i + 1).copied().unwrap_or(wall_str.len());
+                let coord = self.algebraic_to_coord(&wall_str[start..end])?;
+                self.add_wall(coord, orientation, true, true)
+                    .map_err(|error| malformed(&error.to_string()))?;
             }
         }
         
+        // A pawn already on its goal has won
+        self.outcome = numbered.iter()
+            .find(|player| self.goal_positions[player].contains(&self.pawn_positions[player]))
+            .map(|&player| GameResult::Win(player));
+        
         self.update_state_string(true);
+        Ok(())
+    }
+    
+    // Players by number rather than turn order, as state strings list them
+    fn numbered_players(&self) -> Vec<Player> {
+        let mut numbered = self.players.clone();
+        numbered.sort_by_key(|player| player.index());
+        numbered
+    }
+    
+    // Whose turn comes after the player's
+    pub fn next_player(&self, player: Player) -> Player {
+        let position = self.players.iter().position(|&p| p == player)
+            .expect("player is in the game");
+        self.players[(position + 1) % self.players.len()]
     }
     
     pub fn update_state_string(&mut self, keep_player: bool) {
         if !keep_player {
-            self.active_player = self.active_player.opponent();
+            self.active_player = self.next_player(self.active_player);
         }
         
-        let player_char = match self.active_player {
-            Player::Player1 => "1",
-            Player::Player2 => "2",
-        };
-        
         let hwall_str: String = self.hwall_positions.iter()
-            .map(|&pos| self.coord_to_algebraic(pos)[0..2].to_string())
+            .map(|&pos| self.coord_to_algebraic(pos))
             .collect();
             
         let vwall_str: String = self.vwall_positions.iter()
-            .map(|&pos| self.coord_to_algebraic(pos)[0..2].to_string())
+            .map(|&pos| self.coord_to_algebraic(pos))
             .collect();
             
-        let p1_pos = self.coord_to_algebraic(self.pawn_positions[&Player::Player1]);
-        let p2_pos = self.coord_to_algebraic(self.pawn_positions[&Player::Player2]);
-        
-        let p1_walls = self.walls_available[&Player::Player1];
-        let p2_walls = self.walls_available[&Player::Player2];
+        let numbered = self.numbered_players();
+        let pawns: Vec<String> = numbered.iter()
+            .map(|player| self.coord_to_algebraic(self.pawn_positions[player]))
+            .collect();
+        let walls: Vec<String> = numbered.iter()
+            .map(|player| self.walls_available[player].to_string())
+            .collect();
         
         self.state_string = format!(
-            "{} / {} / {} {} / {} {} / {}",
-            hwall_str, vwall_str, p1_pos, p2_pos, p1_walls, p2_walls, player_char
+            "{} / {} / {} / {} / {}",
+            hwall_str, vwall_str, pawns.join(" "), walls.join(" "), self.active_player.index() + 1
         );
     }
     
-    pub fn algebraic_to_coord(&self, square: &str) -> Coord {
-        // Safety check for wall notation
-        if square.len() > 2 && (square.ends_with('h') || square.ends_with('v')) {
-            // println!("Warning: Full wall notation passed to algebraic_to_coord: {}", square);
-            // Extract just the position part
-            let position = &square[0..2];
-            return self.algebraic_to_coord(position);
-        }
-        
-        if square.len() < 2 {
-            panic!("Invalid algebraic notation: {}", square);
-        }
-        
-        let bytes = square.as_bytes();
-        let col_letter = bytes[0] as char;
-        
-        // Check that first character is a valid column letter
-        if !col_letter.is_ascii_alphabetic() {
-            panic!("Invalid column letter in algebraic notation: {}", square);
-        }
-        
-        // Parse row number, ensuring it's all digits
-        let row_digits = &square[1..];
-        let row_num = match row_digits.parse::<usize>() {
-            Ok(num) => num,
-            Err(e) => {
-                panic!("Invalid row number in algebraic notation '{}': {}", square, e);
-            }
+    pub fn algebraic_to_coord(&self, square: &str) -> Result<Coord, QuoridorError> {
+        let square = match square.parse::<Move>()? {
+            Move::Pawn(square) => square,
+            Move::Wall(..) => return Err(QuoridorError::BadNotation(square.to_string())),
         };
         
-        let col = (col_letter.to_ascii_lowercase() as u8 - b'a') as usize;
-        let row = self.size - row_num;
-        
         // Check bounds
-        if row >= self.size || col >= self.size {
-            panic!("Algebraic notation out of bounds: {}", square);
-        }
-        
-        (row, col)
+        self.coord_of(square).ok_or(QuoridorError::OutOfBounds(Move::Pawn(square)))
     }
     
     pub fn coord_to_algebraic(&self, coord: Coord) -> String {
-        let (row, col) = coord;
-        let col_letter = (b'a' + col as u8) as char;
-        let row_number = self.size - row;
-        
-        format!("{}{}", col_letter, row_number)
+        self.square_of(coord).to_string()
+    }
+    
+    // Board coordinate of a named square, if it is on this board
+    pub fn coord_of(&self, square: Square) -> Option<Coord> {
+        square.coord(self.size)
+    }
+    
+    // Name of the square at a board coordinate
+    pub fn square_of(&self, coord: Coord) -> Square {
+        Square::at(self.size, coord)
+    }
+    
+    // Where a move made on this board goes, or where its wall is anchored
+    pub fn move_coord(&self, mv: Move) -> Coord {
+        self.coord_of(mv.square()).expect("move is on the board")
     }
     
     pub fn has_path(&self, player: Player, destination: Coord) -> bool {
@@ -300,283 +846,372 @@ impl Quoridor {
         has_path_connecting(&self.graph, start_idx, end_idx, None)
     }
     
-    pub fn get_wall_edges(&self, wall_move: &str) -> Vec<(Coord, Coord)> {
-        if wall_move.len() < 3 {
-            println!("Invalid wall move format: {}", wall_move);
-            return Vec::new();
-        }
-        
-        // Extract position part (first 2 characters)
-        let position = &wall_move[0..2];
-        let orientation = &wall_move[2..];
-        
-        // Validate orientation
-        if orientation != "h" && orientation != "v" {
-            println!("Invalid wall orientation: {}", orientation);
-            return Vec::new();
+    // Edges cut by a wall. A wall anchored at (row, col) runs along the
+    // groove above (horizontal) or to the right of (vertical) the anchor and
+    // the squares to its right or above it, one edge per square of the wall's
+    // length, so the anchor must leave room for the whole wall on the board.
+    pub fn get_wall_edges(&self, coord: Coord, orientation: Orientation) -> Result<Vec<(Coord, Coord)>, QuoridorError> {
+        if !self.wall_on_board(coord, orientation) {
+            return Err(QuoridorError::OutOfBounds(Move::Wall(self.square_of(coord), orientation)));
         }
         
-        // Parse position without orientation
-        let coord = match self.algebraic_to_coord(position) {
-            c => c,
-            #[allow(unreachable_patterns)]
-            _ => {
-                println!("Failed to parse position: {}", position);
-                return Vec::new();
-            }
-        };
-        
-        let mut edges = Vec::new();
-        
-        if orientation == "h" {
-            if coord.0 > 0 {
-                edges.push((coord, (coord.0 - 1, coord.1)));
-                if coord.1 + 1 < self.size {
-                    edges.push(((coord.0, coord.1 + 1), (coord.0 - 1, coord.1 + 1)));
-                }
-            }
-        } else if orientation == "v" {
-            if coord.1 + 1 < self.size {
-                edges.push((coord, (coord.0, coord.1 + 1)));
-                if coord.0 > 0 {
-                    edges.push(((coord.0 - 1, coord.1), (coord.0 - 1, coord.1 + 1)));
-                }
-            }
+        Ok(self.wall_edges(coord, orientation).collect())
+    }
+    
+    fn wall_on_board(&self, (row, col): Coord, orientation: Orientation) -> bool {
+        let length = self.rules.wall_length;
+        match orientation {
+            Orientation::Horizontal => (1..self.size).contains(&row) && col + length <= self.size,
+            Orientation::Vertical => (length - 1..self.size).contains(&row) && col + 1 < self.size,
         }
-        
-        edges
     }
     
-    pub fn add_wall(&mut self, wall_move: &str, initialise: bool, check: bool) -> bool {
-        if wall_move.len() < 3 {
-            println!("Invalid wall move: {}", wall_move);
-            return false;
+    // Unchecked `get_wall_edges` for anchors already known to be on the board
+    fn wall_edges(&self, (row, col): Coord, orientation: Orientation) -> impl Iterator<Item = (Coord, Coord)> {
+        (0..self.rules.wall_length).map(move |i| match orientation {
+            Orientation::Horizontal => ((row, col + i), (row - 1, col + i)),
+            Orientation::Vertical => ((row - i, col), (row - i, col + 1)),
+        })
+    }
+    
+    // Put a wall on the board: record it, claim its slots and cut its edges
+    fn place_wall(&mut self, coord: Coord, orientation: Orientation) {
+        match orientation {
+            Orientation::Horizontal => self.hwall_positions.push(coord),
+            Orientation::Vertical => self.vwall_positions.push(coord),
         }
+        self.wall_slots.place(coord, orientation);
+        let edges: Vec<_> = self.wall_edges(coord, orientation).collect();
+        self.remove_edges(&edges);
+    }
+    
+    pub fn add_wall(&mut self, coord: Coord, orientation: Orientation, initialise: bool, check: bool) -> Result<(), QuoridorError> {
+        self.get_wall_edges(coord, orientation)?;
         
-        let position = &wall_move[0..2];
-        let orientation = &wall_move[2..];
-        
-        if orientation != "h" && orientation != "v" {
-            println!("Invalid wall orientation: {}", orientation);
-            return false;
+        if check {
+            if initialise {
+                // Setting up a position spends no wall, so only where it goes matters
+                self.placement_check(coord, orientation)?;
+            } else {
+                self.wall_check(self.active_player, coord, orientation)?;
+            }
         }
         
-        // Only parse the position part (first 2 characters)
-        let coord = match self.algebraic_to_coord(position) {
-            c => c,
-            #[allow(unreachable_patterns)]
-            _ => return false,
-        };
-        
-        let edges = self.get_wall_edges(wall_move);
-        if edges.is_empty() {
-            return false;
+        if !initialise {
+            self.make_move(Move::Wall(self.square_of(coord), orientation));
+            self.redo_stack.clear();
+            self.update_state_string(true);
+            return Ok(());
         }
         
-        if check && !self.wall_check(self.active_player, wall_move) {
-            return false;
+        // Setting up a position: place the wall without spending it or passing the turn
+        self.place_wall(coord, orientation);
+        if let Some(key) = self.position_keys.last_mut() {
+            *key ^= zobrist::wall(coord, orientation);
         }
+        self.update_state_string(true);
         
-        // Add wall to appropriate list
-        if orientation == "h" {
-            self.hwall_positions.push(coord);
-        } else if orientation == "v" {
-            self.vwall_positions.push(coord);
-        }
+        Ok(())
+    }
+    
+    fn remove_edges(&mut self, edges: &[(Coord, Coord)]) {
+        // Cutting an edge between squares at the same distance from a goal
+        // cannot lengthen any path to it, so only fields where the edge lies
+        // on some shortest path need rebuilding
+        self.distance_fields.get_mut().retain(|_, field| {
+            edges.iter().all(|(from, to)| {
+                field[self.node_indices[from].index()] == field[self.node_indices[to].index()]
+            })
+        });
         
-        // Remove edges from graph
         for (from, to) in edges {
-            if self.node_indices.contains_key(&from) && self.node_indices.contains_key(&to) {
-                let from_idx = self.node_indices[&from];
-                let to_idx = self.node_indices[&to];
-                
-                // Find and remove the edge
-                if let Some(edge_idx) = self.graph.find_edge(from_idx, to_idx) {
-                    self.graph.remove_edge(edge_idx);
-                }
+            let from_idx = self.node_indices[from];
+            let to_idx = self.node_indices[to];
+            
+            // Find and remove the edge
+            if let Some(edge_idx) = self.graph.find_edge(from_idx, to_idx) {
+                self.graph.remove_edge(edge_idx);
             }
         }
-        
-        if !initialise {
-            self.previous_state = self.state_string.clone();
-            *self.walls_available.get_mut(&self.active_player).unwrap() -= 1;
-            self.last_move = wall_move.to_string();
-            self.update_state_string(false);
-        } else {
-            self.update_state_string(true);
-        }
-        
-        true
     }
     
-    pub fn wall_check(&self, player: Player, wall_move: &str) -> bool {
-        let edges = self.get_wall_edges(wall_move);
+    // Whether cutting the edges would leave some pawn with no way to its goal
+    // row. A pawn whose route (see `shortest_routes`) survives the cut still
+    // gets home, so only a wall across a route pays for a search: one
+    // multi-source BFS out from the goal row with the edges cut.
+    fn blocks_a_path(&self, edges: &[(Coord, Coord)], routes: &HashMap<Player, Vec<(Coord, Coord)>>) -> bool {
+        routes.iter().any(|(player, route)| {
+            let crosses_route = edges.iter()
+                .any(|&(from, to)| route.contains(&(from, to)) || route.contains(&(to, from)));
+            crosses_route && {
+                let pawn_idx = self.node_indices[&self.pawn_positions[player]];
+                !self.reaches(&self.goal_positions[player], pawn_idx, edges)
+            }
+        })
+    }
+    
+    pub fn wall_check(&self, player: Player, coord: Coord, orientation: Orientation) -> Result<(), QuoridorError> {
+        self.get_wall_edges(coord, orientation)?;
         
         // Check if player has walls available
         if self.walls_available[&player] == 0 {
-            return false;
+            return Err(QuoridorError::NoWallsLeft(player));
         }
         
-        // Check if position already has a wall of different orientation
-        let position = &wall_move[0..2];
-        let orientation = &wall_move[2..];
+        self.placement_check(coord, orientation)
+    }
+    
+    // Whether a wall could stand here, whoever has it to place: on the
+    // board, clear of the walls already placed, and leaving every pawn a path
+    fn placement_check(&self, coord: Coord, orientation: Orientation) -> Result<(), QuoridorError> {
+        let wall = Move::Wall(self.square_of(coord), orientation);
+        let edges = self.get_wall_edges(coord, orientation)?;
         
-        let wall_coord = match self.algebraic_to_coord(position) {
-            c => c,
-            #[allow(unreachable_patterns)]
-            _ => return false,
-        };
+        // Check the wall does not overlap or cross a wall already placed
+        if !self.wall_slots.fits(coord, orientation) {
+            return Err(QuoridorError::WallOverlap(wall));
+        }
         
-        if orientation == "v" {
-            // Check if horizontal wall exists at same position
-            if self.hwall_positions.contains(&wall_coord) {
-                return false;
-            }
-        } else if orientation == "h" {
-            // Check if vertical wall exists at same position
-            if self.vwall_positions.contains(&wall_coord) {
-                return false;
-            }
+        if self.blocks_a_path(&edges, &self.shortest_routes()) {
+            return Err(QuoridorError::PathBlocked(wall));
         }
         
-        // Check if edges exist
-        for (from, to) in &edges {
-            if !self.node_indices.contains_key(from) || !self.node_indices.contains_key(to) {
-                return false;
+        Ok(())
+    }
+    
+    pub fn move_pawn(&mut self, destination: Coord, check: bool) -> Result<(), QuoridorError> {
+        let mv = Move::Pawn(self.square_of(destination));
+        if check {
+            if destination.0 >= self.size || destination.1 >= self.size {
+                return Err(QuoridorError::OutOfBounds(mv));
             }
-            
-            let from_idx = self.node_indices[from];
-            let to_idx = self.node_indices[to];
-            
-            if self.graph.find_edge(from_idx, to_idx).is_none() {
-                return false;
+            if !self.get_legal_moves(self.active_player).contains(&mv) {
+                return Err(QuoridorError::IllegalMove(mv));
             }
         }
         
-        // Create a temporary copy of the graph to check path blocking
-        let mut temp_graph = self.graph.clone();
+        self.make_move(mv);
+        self.redo_stack.clear();
+        self.update_state_string(true);
         
-        // Remove edges temporarily
-        for (from, to) in &edges {
-            let from_idx = self.node_indices[from];
-            let to_idx = self.node_indices[to];
-            
-            if let Some(edge_idx) = temp_graph.find_edge(from_idx, to_idx) {
-                temp_graph.remove_edge(edge_idx);
-            }
+        Ok(())
+    }
+    
+    // Play a move for the active player, rejecting it if it is not legal
+    pub fn apply(&mut self, mv: Move) -> Result<(), QuoridorError> {
+        if let Some(result) = self.outcome {
+            return Err(QuoridorError::GameOver(result));
+        }
+        match mv {
+            Move::Pawn(square) => match self.coord_of(square) {
+                Some(destination) => self.move_pawn(destination, true),
+                None => Err(QuoridorError::OutOfBounds(mv)),
+            },
+            Move::Wall(square, orientation) => match self.coord_of(square) {
+                Some(coord) => self.add_wall(coord, orientation, false, true),
+                None => Err(QuoridorError::OutOfBounds(mv)),
+            },
         }
-        
-        // Check if placing the wall blocks paths to goals
-        for (goal_player, goal_positions) in &self.goal_positions {
-            let player_pos = self.pawn_positions[goal_player];
-            let player_node = self.node_indices[&player_pos];
-            
-            let mut has_path_to_any_goal = false;
-            
-            for &goal in goal_positions {
-                if !self.node_indices.contains_key(&goal) {
-                    continue;
+    }
+    
+    // Play a move already known to be legal, in place, for search code.
+    //
+    // This is the cheap half of a make/unmake pair: it skips legality checks,
+    // leaves the redo line alone and does not refresh `state_string`, which
+    // stays stale until the matching `unmake_move` puts the position back.
+    pub fn make_move(&mut self, mv: Move) {
+        let player = self.active_player;
+        let previous_position = self.pawn_positions[&player];
+        let previous_outcome = self.outcome;
+        let mut key = self.position_key();
+        
+        match mv {
+            Move::Pawn(square) => {
+                let destination = self.coord_of(square).expect("make_move given a square off the board");
+                key ^= zobrist::pawn(player, previous_position) ^ zobrist::pawn(player, destination);
+                self.pawn_positions.insert(player, destination);
+                if previous_outcome.is_none() && self.goal_positions[&player].contains(&destination) {
+                    self.outcome = Some(GameResult::Win(player));
                 }
-                
-                let goal_node = self.node_indices[&goal];
-                
-                if has_path_connecting(&temp_graph, player_node, goal_node, None) {
-                    has_path_to_any_goal = true;
-                    break;
+            },
+            Move::Wall(square, orientation) => {
+                let coord = self.coord_of(square)
+                    .filter(|&coord| self.wall_on_board(coord, orientation))
+                    .expect("make_move given a wall off the board");
+                self.place_wall(coord, orientation);
+                key ^= zobrist::wall(coord, orientation);
+                for (&spender, &count) in &self.walls_available {
+                    if self.rules.shared_wall_pool || spender == player {
+                        key ^= zobrist::walls_left(spender, count) ^ zobrist::walls_left(spender, count - 1);
+                    }
                 }
-            }
-            
-            if !has_path_to_any_goal {
-                return false;
-            }
+                self.spend_walls(player, false);
+            },
         }
         
-        true
+        self.history.push(MoveRecord { mv, player, previous_position, previous_outcome });
+        self.last_move = Some(mv);
+        self.active_player = self.next_player(player);
+        
+        key ^= zobrist::side_to_move(player) ^ zobrist::side_to_move(self.active_player);
+        debug_assert_eq!(key, self.full_position_key(), "position key out of step");
+        self.position_keys.push(key);
+        if self.outcome.is_none() {
+            if self.history.len() >= self.rules.move_limit {
+                self.outcome = Some(GameResult::Draw(DrawReason::MoveLimit));
+            } else if self.repetitions() >= 3 {
+                self.outcome = Some(GameResult::Draw(DrawReason::Repetition));
+            }
+        }
     }
     
-    pub fn move_pawn(&mut self, move_str: &str, check: bool) -> bool {
-        let destination = match self.algebraic_to_coord(move_str) {
-            c => c,
-            #[allow(unreachable_patterns)]
-            _ => return false,
-        };
-        
-        if check {
-            let legal_moves = self.get_legal_moves(self.active_player);
-            if !legal_moves.contains(&move_str.to_string()) {
-                return false;
+    // Take a wall from the player's allowance, or from everyone's with a
+    // shared pool; `refund` puts it back
+    fn spend_walls(&mut self, player: Player, refund: bool) {
+        let shared = self.rules.shared_wall_pool;
+        for (&other, count) in self.walls_available.iter_mut() {
+            if shared || other == player {
+                *count = if refund { *count + 1 } else { *count - 1 };
             }
         }
+    }
+    
+    // Take back the last move made, restoring pawns, walls, graph edges and
+    // wall counts. Like `make_move`, leaves `state_string` and the redo line alone.
+    pub fn unmake_move(&mut self) -> Option<MoveRecord> {
+        let record = self.history.pop()?;
+        
+        match record.mv {
+            Move::Pawn(_) => {
+                self.pawn_positions.insert(record.player, record.previous_position);
+            },
+            Move::Wall(square, orientation) => {
+                let coord = self.coord_of(square).expect("walls in the history are on the board");
+                let walls = match orientation {
+                    Orientation::Horizontal => &mut self.hwall_positions,
+                    Orientation::Vertical => &mut self.vwall_positions,
+                };
+                let removed = walls.pop();
+                debug_assert_eq!(removed, Some(coord), "wall lists out of step with history");
+                self.wall_slots.remove(coord, orientation);
+                
+                // A legal wall only ever cut edges that were present, so all of them go back
+                let edges = self.get_wall_edges(coord, orientation)
+                    .expect("walls in the history are on the board");
+                
+                // Restoring an edge only shortens paths if its ends were more than a step apart
+                self.distance_fields.get_mut().retain(|_, field| {
+                    edges.iter().all(|(from, to)| {
+                        let (a, b) = (field[self.node_indices[from].index()], field[self.node_indices[to].index()]);
+                        a == b || a.checked_add(1) == Some(b) || b.checked_add(1) == Some(a)
+                    })
+                });
+                for (from, to) in edges {
+                    self.graph.update_edge(self.node_indices[&from], self.node_indices[&to], ());
+                }
+                
+                self.spend_walls(record.player, true);
+            },
+        }
+        
+        self.active_player = record.player;
+        self.last_move = self.history.last().map(|r| r.mv);
+        self.outcome = record.previous_outcome;
+        self.position_keys.pop();
         
-        self.pawn_positions.insert(self.active_player, destination);
-        self.previous_state = self.state_string.clone();
-        self.last_move = move_str.to_string();
-        self.update_state_string(false);
+        Some(record)
+    }
+    
+    // Take back the last move so it can be redone. Returns the move that was undone.
+    pub fn undo(&mut self) -> Option<Move> {
+        let record = self.unmake_move()?;
+        self.redo_stack.push(record);
+        self.update_state_string(true);
         
-        true
+        Some(record.mv)
     }
     
-    pub fn get_legal_moves(&self, player: Player) -> Vec<String> {
-        let opponent = player.opponent();
-        let own_pos = self.pawn_positions[&player];
-        let opponent_pos = self.pawn_positions[&opponent];
+    // Replay the most recently undone move. Returns the move that was redone.
+    pub fn redo(&mut self) -> Option<Move> {
+        let record = self.redo_stack.pop()?;
+        self.make_move(record.mv);
+        self.update_state_string(true);
         
+        Some(record.mv)
+    }
+    
+    // Pawn moves under the official rules: a step to any open neighbouring
+    // square; when that square holds another pawn, a jump straight over it,
+    // or, if a wall, the board edge or a further pawn is behind it, a
+    // diagonal step to either side of it that no wall or pawn blocks.
+    pub fn get_legal_moves(&self, player: Player) -> Vec<Move> {
+        let occupied: Vec<Coord> = self.players.iter()
+            .filter(|&&other| other != player)
+            .map(|other| self.pawn_positions[other])
+            .collect();
+        self.pawn_moves(self.pawn_positions[&player], &occupied)
+    }
+    
+    // Moves of a pawn on `own_pos` with the other pawns on `occupied`,
+    // wherever the pawns actually are
+    fn pawn_moves(&self, own_pos: Coord, occupied: &[Coord]) -> Vec<Move> {
         let own_node = self.node_indices[&own_pos];
         let mut legal_moves = Vec::new();
         
         // Get neighbors from the graph
         for neighbor_idx in self.graph.neighbors(own_node) {
             let neighbor_pos = self.graph[neighbor_idx];
+            if !occupied.contains(&neighbor_pos) {
+                legal_moves.push(Move::Pawn(self.square_of(neighbor_pos)));
+                continue;
+            }
             
-            // Skip if it's the opponent's position
-            if neighbor_pos == opponent_pos {
-                // Try to jump over
-                let jump_row = 2 * opponent_pos.0 as i32 - own_pos.0 as i32;
-                let jump_col = 2 * opponent_pos.1 as i32 - own_pos.1 as i32;
-                
-                // Check bounds
-                if jump_row >= 0 && jump_row < self.size as i32 &&
-                jump_col >= 0 && jump_col < self.size as i32 {
-                    let jump_pos = (jump_row as usize, jump_col as usize);
-                    
-                    // If there's a path from opponent to jump position
-                    if self.node_indices.contains_key(&jump_pos) {
-                        let opponent_node = self.node_indices[&opponent_pos];
-                        let jump_node = self.node_indices[&jump_pos];
-                        
-                        if self.graph.contains_edge(opponent_node, jump_node) {
-                            legal_moves.push(self.coord_to_algebraic(jump_pos));
-                        } else {
-                            // If can't jump, can move to opponent's neighbors
-                            for op_neighbor_idx in self.graph.neighbors(opponent_node) {
-                                let op_neighbor_pos = self.graph[op_neighbor_idx];
-                                if op_neighbor_pos != own_pos {
-                                    legal_moves.push(self.coord_to_algebraic(op_neighbor_pos));
-                                }
-                            }
+            // Face to face: the free squares the other pawn could step to are
+            // the candidates, as walls around that pawn block the jumper too
+            let beyond: Vec<Coord> = self.graph.neighbors(neighbor_idx)
+                .map(|idx| self.graph[idx])
+                .filter(|&pos| pos != own_pos && !occupied.contains(&pos))
+                .collect();
+            
+            let jump = (2 * neighbor_pos.0 as isize - own_pos.0 as isize, 2 * neighbor_pos.1 as isize - own_pos.1 as isize);
+            match beyond.iter().find(|&&(row, col)| (row as isize, col as isize) == jump) {
+                Some(&jump_pos) => legal_moves.push(Move::Pawn(self.square_of(jump_pos))),
+                // Straight jump blocked: go diagonally instead
+                None => {
+                    for pos in beyond {
+                        let mv = Move::Pawn(self.square_of(pos));
+                        if !legal_moves.contains(&mv) {
+                            legal_moves.push(mv);
                         }
                     }
-                }
-                
-                continue;
+                },
             }
-            
-            legal_moves.push(self.coord_to_algebraic(neighbor_pos));
         }
         
         legal_moves
     }
     
-    pub fn get_legal_walls(&self, player: Player) -> Vec<String> {
+    // Same walls `wall_check` accepts, without building an error per rejected
+    // slot: taken slots are dropped by lookup in `wall_slots`, and only walls
+    // across a shortest path pay for a search
+    pub fn get_legal_walls(&self, player: Player) -> Vec<Move> {
         let mut legal_walls = Vec::new();
+        if self.walls_available[&player] == 0 {
+            return legal_walls;
+        }
+        let routes = self.shortest_routes();
+        let mut edges = Vec::with_capacity(self.rules.wall_length);
         
-        // Iterate through all possible wall positions
         for row in 1..self.size {
             for col in 0..(self.size - 1) {
-                for orientation in &["h", "v"] {
-                    let wall_move = format!("{}{}", self.coord_to_algebraic((row, col)), orientation);
-                    if self.wall_check(player, &wall_move) {
-                        legal_walls.push(wall_move);
+                for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+                    if !self.wall_on_board((row, col), orientation) || !self.wall_slots.fits((row, col), orientation) {
+                        continue;
+                    }
+                    edges.clear();
+                    edges.extend(self.wall_edges((row, col), orientation));
+                    if !self.blocks_a_path(&edges, &routes) {
+                        legal_walls.push(Move::Wall(self.square_of((row, col)), orientation));
                     }
                 }
             }
@@ -585,67 +1220,204 @@ impl Quoridor {
         legal_walls
     }
     
-    pub fn distance_to_goal(&self, player: Player) -> usize {
-        let start_pos = self.pawn_positions[&player];
-        let start_idx = self.node_indices[&start_pos];
-        let goal_positions = &self.goal_positions[&player];
-        
-        let mut min_distance = usize::MAX;
-        
-        // Calculate shortest paths to all nodes
-        let distances = dijkstra(&self.graph, start_idx, None, |_| 1);
+    // All legal moves for the active player: pawn moves first, then walls
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let player = self.active_player;
+        let mut moves = self.get_legal_moves(player);
+        if self.walls_available[&player] > 0 {
+            moves.extend(self.get_legal_walls(player));
+        }
+        moves
+    }
+    
+    // Breadth-first distance from the nearest of `sources` to every square, by
+    // node index, ignoring the `cut` edges. Unreachable squares get usize::MAX.
+    fn goal_distances(&self, sources: &[Coord], cut: &[(Coord, Coord)]) -> Vec<usize> {
+        let mut distances = vec![usize::MAX; self.graph.node_count()];
+        let mut queue = VecDeque::new();
+        for source in sources {
+            let idx = self.node_indices[source];
+            distances[idx.index()] = 0;
+            queue.push_back(idx);
+        }
+        
+        while let Some(node) = queue.pop_front() {
+            let from = self.graph[node];
+            for next in self.graph.neighbors(node) {
+                let to = self.graph[next];
+                if distances[next.index()] != usize::MAX
+                    || cut.iter().any(|&edge| edge == (from, to) || edge == (to, from)) {
+                    continue;
+                }
+                distances[next.index()] = distances[node.index()] + 1;
+                queue.push_back(next);
+            }
+        }
         
-        // Find minimum distance to any goal
-        for &goal in goal_positions {
-            if let Some(&goal_idx) = self.node_indices.get(&goal) {
-                if let Some(&distance) = distances.get(&goal_idx) {
-                    min_distance = min_distance.min(distance);
+        distances
+    }
+    
+    // Same search as `goal_distances`, stopping as soon as `target` is found
+    fn reaches(&self, sources: &[Coord], target: NodeIndex, cut: &[(Coord, Coord)]) -> bool {
+        let mut seen = vec![false; self.graph.node_count()];
+        let mut queue = VecDeque::new();
+        for source in sources {
+            let idx = self.node_indices[source];
+            seen[idx.index()] = true;
+            queue.push_back(idx);
+        }
+        
+        while let Some(node) = queue.pop_front() {
+            if node == target {
+                return true;
+            }
+            let from = self.graph[node];
+            for next in self.graph.neighbors(node) {
+                let to = self.graph[next];
+                if seen[next.index()] || cut.iter().any(|&edge| edge == (from, to) || edge == (to, from)) {
+                    continue;
                 }
+                seen[next.index()] = true;
+                queue.push_back(next);
             }
         }
         
-        if min_distance == usize::MAX { 100 } else { min_distance }
+        false
     }
     
-    pub fn win_check(&self, move_str: &str) -> bool {
-        match self.algebraic_to_coord(move_str) {
-            (row, _) => match self.active_player {
-                Player::Player1 => row == 0,
-                Player::Player2 => row == self.size - 1,
-            },
-            #[allow(unreachable_patterns)]
-            _ => false,
+    // Run `f` on the player's distance field, building it first if stale
+    fn with_distance_field<T>(&self, player: Player, f: impl FnOnce(&[usize]) -> T) -> T {
+        let mut fields = self.distance_fields.borrow_mut();
+        let field = fields.entry(player)
+            .or_insert_with(|| self.goal_distances(&self.goal_positions[&player], &[]));
+        f(field)
+    }
+    
+    // One shortest route from the player's pawn to its goal row, as the
+    // edges it steps along, found by walking down the distance field. Empty
+    // for a pawn that is cut off.
+    pub fn shortest_route(&self, player: Player) -> Vec<(Coord, Coord)> {
+        let mut node = self.node_indices[&self.pawn_positions[&player]];
+        self.with_distance_field(player, |field| {
+            let mut route = Vec::new();
+            if field[node.index()] == usize::MAX {
+                return route;
+            }
+            while field[node.index()] > 0 {
+                let next = self.graph.neighbors(node)
+                    .find(|next| field[next.index()] == field[node.index()] - 1)
+                    .expect("a square off the goal row has a neighbour one step nearer");
+                route.push((self.graph[node], self.graph[next]));
+                node = next;
+            }
+            route
+        })
+    }
+    
+    fn shortest_routes(&self) -> HashMap<Player, Vec<(Coord, Coord)>> {
+        self.goal_positions.keys().map(|&player| (player, self.shortest_route(player))).collect()
+    }
+    
+    // Whether the wall would cut one of the route's steps
+    pub fn wall_crosses(&self, coord: Coord, orientation: Orientation, route: &[(Coord, Coord)]) -> bool {
+        self.wall_on_board(coord, orientation) && self.wall_edges(coord, orientation)
+            .any(|(from, to)| route.contains(&(from, to)) || route.contains(&(to, from)))
+    }
+    
+    pub fn distance_to_goal(&self, player: Player) -> usize {
+        self.distance_from(player, self.pawn_positions[&player])
+    }
+    
+    // Steps from `square` to the player's goal, as `distance_to_goal`
+    pub fn distance_from(&self, player: Player, square: Coord) -> usize {
+        let idx = self.node_indices[&square];
+        
+        match self.with_distance_field(player, |field| field[idx.index()]) {
+            usize::MAX => 100,
+            distance => distance,
+        }
+    }
+    
+    // How the game ended, or None while it is still being played. Kept up to
+    // date by every move, take-back and state string.
+    pub fn outcome(&self) -> Option<GameResult> {
+        self.outcome
+    }
+    
+    // Zobrist key (see `zobrist`) of everything that decides how play can go
+    // on: pawns, walls, wall counts and the side to move, but not the order
+    // the walls went down in. Kept up to date move by move.
+    pub fn position_key(&self) -> u64 {
+        self.position_keys.last().copied().unwrap_or_else(|| self.full_position_key())
+    }
+    
+    // `position_key` built from scratch
+    fn full_position_key(&self) -> u64 {
+        let mut key = zobrist::side_to_move(self.active_player) ^ self.wall_key();
+        for &player in &self.players {
+            key ^= zobrist::pawn(player, self.pawn_positions[&player]);
+            key ^= zobrist::walls_left(player, self.walls_available[&player]);
+        }
+        key
+    }
+    
+    // The walls' part of `position_key`: the same for any position with
+    // the same walls down
+    pub fn wall_key(&self) -> u64 {
+        let horizontal = self.hwall_positions.iter().map(|&coord| zobrist::wall(coord, Orientation::Horizontal));
+        let vertical = self.vwall_positions.iter().map(|&coord| zobrist::wall(coord, Orientation::Vertical));
+        horizontal.chain(vertical).fold(0, |key, wall| key ^ wall)
+    }
+    
+    // How many times the current position has come up in this game, itself
+    // included. Walls never come off the board, so only positions since the
+    // last wall can match. Three is a draw.
+    pub fn repetitions(&self) -> usize {
+        let current = self.position_keys.last().copied();
+        let pawn_moves = self.history.iter().rev()
+            .take_while(|record| matches!(record.mv, Move::Pawn(_)))
+            .count();
+        self.position_keys.iter().rev()
+            .take(pawn_moves + 1)
+            .filter(|&&key| Some(key) == current)
+            .count()
+    }
+    
+    // Whether the move takes the active player's pawn onto its goal row
+    pub fn win_check(&self, mv: Move) -> bool {
+        match mv {
+            Move::Pawn(square) => self.coord_of(square)
+                .is_some_and(|destination| self.goal_positions[&self.active_player].contains(&destination)),
+            Move::Wall(..) => false,
         }
     }
     
+    // The opponent nearest their goal; in a two-player game, simply the opponent
+    pub fn leading_opponent(&self, player: Player) -> Player {
+        self.players.iter()
+            .copied()
+            .filter(|&other| other != player)
+            .min_by_key(|&other| self.distance_to_goal(other))
+            .expect("every game has at least two players")
+    }
+    
     pub fn moves_to_next_row(&self, player: Player) -> usize {
         let curr_pos = self.pawn_positions[&player];
         let curr_idx = self.node_indices[&curr_pos];
         
-        // Determine target row based on player direction
-        let next_row = match player {
-            Player::Player1 => {
-                if curr_pos.0 > 0 { curr_pos.0 - 1 } else { return 100 }
-            },
-            Player::Player2 => {
-                if curr_pos.0 < self.size - 1 { curr_pos.0 + 1 } else { return 100 }
-            },
+        // Determine the next row (or column, for players 3 and 4) toward the goal edge
+        let (row, col) = curr_pos;
+        let last = self.size - 1;
+        let row_squares: Vec<Coord> = match player {
+            Player::Player1 if row > 0 => (0..self.size).map(|c| (row - 1, c)).collect(),
+            Player::Player2 if row < last => (0..self.size).map(|c| (row + 1, c)).collect(),
+            Player::Player3 if col < last => (0..self.size).map(|r| (r, col + 1)).collect(),
+            Player::Player4 if col > 0 => (0..self.size).map(|r| (r, col - 1)).collect(),
+            _ => return 100,
         };
         
-        let mut min_dist = usize::MAX;
-        
-        // Calculate distances to all nodes
-        let distances = dijkstra(&self.graph, curr_idx, None, |_| 1);
-        
-        // Find minimum distance to any node in the next row
-        for col in 0..self.size {
-            let target = (next_row, col);
-            if let Some(&target_idx) = self.node_indices.get(&target) {
-                if let Some(&distance) = distances.get(&target_idx) {
-                    min_dist = min_dist.min(distance);
-                }
-            }
-        }
+        let distances = self.goal_distances(&row_squares, &[]);
+        let min_dist = distances[curr_idx.index()];
         
         if min_dist == usize::MAX { 100 } else { min_dist }
     }
@@ -654,18 +1426,23 @@ impl Quoridor {
 // Strategy trait
 pub trait Strategy {
     fn name(&self) -> String;
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String>;
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move>;
+    
+    // What the last search behind `choose_move` found, for strategies that search
+    fn search_info(&self) -> Option<SearchInfo> {
+        None
+    }
 }
 
 // Base implementation for all strategies
 pub struct QuoridorStrategy {
     name: String,
-    opening_moves: Vec<String>,
+    opening_moves: Vec<Move>,
     move_counter: usize,
 }
 
 impl QuoridorStrategy {
-    pub fn new(name: &str, opening_name: &str, opening_moves: Vec<String>) -> Self {
+    pub fn new(name: &str, opening_name: &str, opening_moves: Vec<Move>) -> Self {
         let full_name = if opening_moves.is_empty() {
             name.to_string()
         } else {
@@ -679,36 +1456,23 @@ impl QuoridorStrategy {
         }
     }
     
-    pub fn try_opening_move(&mut self, game: &Quoridor) -> Option<String> {
+    // Add "@evaluator" to the name, before the opening's
+    fn name_evaluator(&mut self, evaluator: &dyn Evaluator) {
+        let end = self.name.find('-').unwrap_or(self.name.len());
+        self.name.insert_str(end, &format!("@{}", evaluator.name()));
+    }
+    
+    pub fn try_opening_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try to use opening move if available
         if self.move_counter < self.opening_moves.len() {
-            let move_str = self.opening_moves[self.move_counter].clone();
-            // println!("Trying opening move: {} (player: {})", move_str, game.active_player.name());
+            let mv = self.opening_moves[self.move_counter];
             
             self.move_counter += 1;
             
-            // Check if the opening move is a wall move
-            let is_wall_move = move_str.len() == 3 && 
-                            (move_str.ends_with('h') || move_str.ends_with('v'));
-            
-            // Get legal moves
-            let legal_pawn_moves = game.get_legal_moves(game.active_player);
-            let legal_wall_moves = if game.walls_available[&game.active_player] > 0 {
-                game.get_legal_walls(game.active_player)
+            if game.legal_moves().contains(&mv) {
+                return Some(mv);
             } else {
-                Vec::new()
-            };
-            
-            let all_legal_moves: Vec<String> = legal_pawn_moves.iter().cloned()
-                .chain(legal_wall_moves.iter().cloned())
-                .collect();
-            
-            if all_legal_moves.contains(&move_str) {
-                // println!("Opening move {} is legal", move_str);
-                return Some(move_str);
-            } else {
-                println!("Opening move {} is NOT legal", move_str);
-                // println!("Legal moves are: {:?}", all_legal_moves);
+                println!("Opening move {} is NOT legal", mv);
             }
         }
         
@@ -722,7 +1486,7 @@ pub struct RandomStrategy {
 }
 
 impl RandomStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>) -> Self {
         RandomStrategy {
             base: QuoridorStrategy::new("Random", opening_name, opening_moves),
         }
@@ -734,29 +1498,20 @@ impl Strategy for RandomStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
         }
         
         // Otherwise choose randomly
-        let legal_pawn_moves = game.get_legal_moves(game.active_player);
-        let legal_wall_moves = if game.walls_available[&game.active_player] > 0 {
-            game.get_legal_walls(game.active_player)
-        } else {
-            Vec::new()
-        };
-        
-        let all_legal_moves: Vec<String> = legal_pawn_moves.into_iter()
-            .chain(legal_wall_moves.into_iter())
-            .collect();
+        let all_legal_moves = game.legal_moves();
         
         if all_legal_moves.is_empty() {
             None
         } else {
             let mut rng = rand::thread_rng();
-            Some(all_legal_moves[rng.gen_range(0..all_legal_moves.len())].clone())
+            Some(all_legal_moves[rng.gen_range(0..all_legal_moves.len())])
         }
     }
 }
@@ -767,7 +1522,7 @@ pub struct ShortestPathStrategy {
 }
 
 impl ShortestPathStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>) -> Self {
         ShortestPathStrategy {
             base: QuoridorStrategy::new("ShortestPath", opening_name, opening_moves),
         }
@@ -779,10 +1534,10 @@ impl Strategy for ShortestPathStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
         }
         
         // Get legal pawn moves
@@ -795,20 +1550,22 @@ impl Strategy for ShortestPathStrategy {
         let mut best_move = None;
         let mut best_distance = usize::MAX;
         
-        for move_str in &legal_pawn_moves {
+        // One scratch copy of the game to simulate moves on
+        let mut temp_game = game.clone();
+        
+        for &mv in &legal_pawn_moves {
             // Check for win
-            if game.win_check(move_str) {
-                return Some(move_str.clone());
+            if game.win_check(mv) {
+                return Some(mv);
             }
             
-            // Create a copy of the game to simulate the move
-            let mut temp_game = game.clone();
-            temp_game.move_pawn(move_str, false);
-            
+            temp_game.make_move(mv);
             let distance = temp_game.distance_to_goal(player);
+            temp_game.unmake_move();
+            
             if distance < best_distance {
                 best_distance = distance;
-                best_move = Some(move_str.clone());
+                best_move = Some(mv);
             }
         }
         
@@ -824,7 +1581,7 @@ pub struct DefensiveStrategy {
 }
 
 impl DefensiveStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>, wall_preference: f64) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>, wall_preference: f64) -> Self {
         DefensiveStrategy {
             base: QuoridorStrategy::new("Defensive", opening_name, opening_moves),
             wall_preference,
@@ -838,14 +1595,14 @@ impl Strategy for DefensiveStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
         }
         
         let player = game.active_player;
-        let opponent = player.opponent();
+        let opponent = game.leading_opponent(player);
         
         let legal_wall_moves = if game.walls_available[&player] > 0 {
             game.get_legal_walls(player)
@@ -860,21 +1617,21 @@ impl Strategy for DefensiveStrategy {
             
             // Find walls that would increase opponent's distance
             let mut blocking_walls = Vec::new();
+            let mut temp_game = game.clone();
             
-            for wall_move in &legal_wall_moves {
-                let mut temp_game = game.clone();
-                temp_game.add_wall(wall_move, false, false);
-                
+            for &wall_move in &legal_wall_moves {
+                temp_game.make_move(wall_move);
                 let new_distance = temp_game.distance_to_goal(opponent);
+                temp_game.unmake_move();
                 
                 if new_distance > opponent_distance {
-                    blocking_walls.push(wall_move.clone());
+                    blocking_walls.push(wall_move);
                 }
             }
             
             if !blocking_walls.is_empty() {
                 let mut rng = rand::thread_rng();
-                return Some(blocking_walls[rng.gen_range(0..blocking_walls.len())].clone());
+                return Some(blocking_walls[rng.gen_range(0..blocking_walls.len())]);
             }
         }
         
@@ -892,7 +1649,7 @@ pub struct BalancedStrategy {
 }
 
 impl BalancedStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>, defense_weight: f64) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>, defense_weight: f64) -> Self {
         BalancedStrategy {
             base: QuoridorStrategy::new("Balanced", opening_name, opening_moves),
             defense_weight,
@@ -907,10 +1664,10 @@ impl Strategy for BalancedStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
         }
         
         let player = game.active_player;
@@ -929,16 +1686,24 @@ pub struct AdaptiveStrategy {
     base: QuoridorStrategy,
     defensive_strategy: DefensiveStrategy,
     offensive_strategy: ShortestPathStrategy,
+    evaluator: Box<dyn Evaluator>, // Ahead when positive
 }
 
 impl AdaptiveStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>) -> Self {
         AdaptiveStrategy {
             base: QuoridorStrategy::new("Adaptive", opening_name, opening_moves),
             defensive_strategy: DefensiveStrategy::new("", Vec::new(), 0.7),
             offensive_strategy: ShortestPathStrategy::new("", Vec::new()),
+            evaluator: Box::new(PathDifferenceEvaluator),
         }
     }
+    
+    pub fn with_evaluator(mut self, evaluator: Box<dyn Evaluator>) -> Self {
+        self.base.name_evaluator(evaluator.as_ref());
+        self.evaluator = evaluator;
+        self
+    }
 }
 
 impl Strategy for AdaptiveStrategy {
@@ -946,21 +1711,14 @@ impl Strategy for AdaptiveStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
         }
         
-        let player = game.active_player;
-        let opponent = player.opponent();
-        
-        // Calculate distances to goal for both players
-        let player_distance = game.distance_to_goal(player);
-        let opponent_distance = game.distance_to_goal(opponent);
-        
         // If we're closer to winning, play offensively
-        if player_distance < opponent_distance {
+        if self.evaluator.evaluate(game, game.active_player) > 0.0 {
             self.offensive_strategy.choose_move(game)
         } else {
             // Otherwise play defensively
@@ -969,128 +1727,571 @@ impl Strategy for AdaptiveStrategy {
     }
 }
 
+// Transposition table size the tournament and wasm build give the search strategies
+pub const DEFAULT_TABLE_MB: usize = 16;
+
+// Deepest an iterative-deepening search goes, whatever its budget
+pub const MAX_SEARCH_DEPTH: usize = 64;
+
+// Score of a won game, above anything the evaluation gives. A search takes
+// off the moves from the root to the win, so quicker wins (and slower
+// losses) score better, and anything past WIN_BOUND is a win.
+pub const WIN_SCORE: f64 = 10_000.0;
+pub const WIN_BOUND: f64 = WIN_SCORE / 2.0;
+
+// Half-width of the window an iteration searches around the last one's score
+const ASPIRATION_WINDOW: f64 = 10.0;
+
+// Without a clock in the browser, time budgets become node budgets at this rate
+#[cfg(target_arch = "wasm32")]
+const WASM_NODES_PER_SECOND: f64 = 20_000.0;
+
+// Budget for an iterative-deepening search, written "2s", "250ms", "100kn"
+// (thousand nodes) or "5000n" in strategy names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchLimit {
+    Time(Duration),
+    Nodes(usize),
+}
+
+impl fmt::Display for SearchLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchLimit::Time(time) if time.subsec_millis() == 0 => write!(f, "{}s", time.as_secs()),
+            SearchLimit::Time(time) => write!(f, "{}ms", time.as_millis()),
+            SearchLimit::Nodes(nodes) if nodes.is_multiple_of(1000) => write!(f, "{}kn", nodes / 1000),
+            SearchLimit::Nodes(nodes) => write!(f, "{}n", nodes),
+        }
+    }
+}
+
+impl FromStr for SearchLimit {
+    type Err = QuoridorError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || QuoridorError::BadNotation(s.to_string());
+        let number = |digits: &str| digits.parse::<f64>().ok().filter(|n| n.is_finite() && *n > 0.0).ok_or_else(bad);
+        
+        if let Some(digits) = s.strip_suffix("ms") {
+            Ok(SearchLimit::Time(Duration::from_secs_f64(number(digits)? / 1000.0)))
+        } else if let Some(digits) = s.strip_suffix('s') {
+            Ok(SearchLimit::Time(Duration::from_secs_f64(number(digits)?)))
+        } else if let Some(digits) = s.strip_suffix("kn") {
+            Ok(SearchLimit::Nodes((number(digits)? * 1000.0) as usize))
+        } else if let Some(digits) = s.strip_suffix('n') {
+            Ok(SearchLimit::Nodes(number(digits)? as usize))
+        } else {
+            Err(bad())
+        }
+    }
+}
+
+// Weights of the evaluation's features (strategy C3 from the paper), whose
+// weights are the defaults. Saved as one "name value" line per weight, so a
+// tuned set (see `tuning`) can be kept in a file and loaded by Minimax.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvalWeights {
+    pub position_difference: f64, // f2: opponent's distance to goal minus ours
+    pub attacking: f64,           // f3: how soon we reach the next row
+    pub defensive: f64,           // f4: opponent's moves to their next row
+}
+
+impl EvalWeights {
+    pub const PAPER: EvalWeights = EvalWeights {
+        position_difference: 0.6001,
+        attacking: 14.45,
+        defensive: 6.52,
+    };
+    
+    const NAMES: [&'static str; 3] = ["position_difference", "attacking", "defensive"];
+    
+    pub fn to_array(self) -> [f64; 3] {
+        [self.position_difference, self.attacking, self.defensive]
+    }
+    
+    pub fn from_array([position_difference, attacking, defensive]: [f64; 3]) -> Self {
+        EvalWeights { position_difference, attacking, defensive }
+    }
+    
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        std::fs::read_to_string(path)?
+            .parse()
+            .map_err(|e: QuoridorError| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+    
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self::PAPER
+    }
+}
+
+impl fmt::Display for EvalWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, weight) in Self::NAMES.iter().zip(self.to_array()) {
+            writeln!(f, "{} {}", name, weight)?;
+        }
+        Ok(())
+    }
+}
+
+// Every weight must be given once; blank lines and '#' comments are skipped
+impl FromStr for EvalWeights {
+    type Err = QuoridorError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = [None; 3];
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let malformed = || QuoridorError::MalformedWeights(line.to_string());
+            let (name, value) = line.split_once(char::is_whitespace).ok_or_else(malformed)?;
+            let index = Self::NAMES.iter().position(|&known| known == name).ok_or_else(malformed)?;
+            let value = value.trim().parse::<f64>().ok().filter(|value| value.is_finite()).ok_or_else(malformed)?;
+            if weights[index].replace(value).is_some() {
+                return Err(QuoridorError::MalformedWeights(format!("{} given twice", name)));
+            }
+        }
+        
+        let mut values = [0.0; 3];
+        for (i, weight) in weights.into_iter().enumerate() {
+            values[i] = weight.ok_or_else(|| QuoridorError::MalformedWeights(format!("{} missing", Self::NAMES[i])))?;
+        }
+        Ok(Self::from_array(values))
+    }
+}
+
+// What a search found and what it cost
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchInfo {
+    pub best_move: Option<Move>,
+    pub score: f64,     // For the side to move; wins score WIN_SCORE less the moves to them
+    pub pv: Vec<Move>,  // Principal variation: the best move and the expected replies
+    pub depth: usize,   // Deepest search completed
+    pub nodes: usize,   // Positions visited
+    pub time: Duration, // Zero where there is no clock (wasm)
+    pub table_hits: usize,
+}
+
+impl fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pv: Vec<String> = self.pv.iter().map(|mv| mv.to_string()).collect();
+        write!(
+            f,
+            "depth {} score {:.2} nodes {} time {:.2?} table hits {} pv {}",
+            self.depth, self.score, self.nodes, self.time, self.table_hits, pv.join(" ")
+        )
+    }
+}
+
 // Minimax Strategy
 pub struct MinimaxStrategy {
     base: QuoridorStrategy,
-    depth: usize
+    depth: usize,               // Fixed depth, or the deepest iteration under a limit
+    limit: Option<SearchLimit>, // Deepen one ply at a time until this runs out
+    evaluator: Box<dyn Evaluator>,
+    table: TranspositionTable,
+    ordering: MoveOrdering,
+    races: PawnRaceCache,
+    nodes: usize,               // Positions visited by the current search
+    table_hits: usize,          // Positions the current search found in the table
+    #[cfg(not(target_arch = "wasm32"))]
+    deadline: Option<Instant>,
+    stopped: bool,              // The budget ran out; the search is unwinding
+    last_search: Option<SearchInfo>,
 }
 
 impl MinimaxStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>, depth: usize) -> Self {
+    // `table_mb` megabytes of transposition table; 0 searches without one.
+    // Searches at least one ply deep, whatever `depth` says.
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>, depth: usize, table_mb: usize) -> Self {
+        let depth = depth.max(1);
         let name = format!("Minimax{}", depth);
         
         MinimaxStrategy {
             base: QuoridorStrategy::new(&name, opening_name, opening_moves),
-            depth: depth
+            depth,
+            limit: None,
+            evaluator: Box::new(C3Evaluator::default()),
+            table: TranspositionTable::new(table_mb),
+            ordering: MoveOrdering::new(),
+            races: PawnRaceCache::new(),
+            nodes: 0,
+            table_hits: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            deadline: None,
+            stopped: false,
+            last_search: None,
+        }
+    }
+    
+    // Iterative deepening: search to depth 1, 2, 3... until the budget runs
+    // out, trying each iteration's best move first in the next, and play the
+    // best move of the deepest iteration that finished
+    pub fn iterative(opening_name: &str, opening_moves: Vec<Move>, limit: SearchLimit, table_mb: usize) -> Self {
+        let mut strategy = Self::new(opening_name, opening_moves, MAX_SEARCH_DEPTH, table_mb);
+        strategy.base.name = format!("IDMinimax{}", limit);
+        
+        #[cfg(target_arch = "wasm32")]
+        let limit = match limit {
+            SearchLimit::Time(time) => SearchLimit::Nodes((time.as_secs_f64() * WASM_NODES_PER_SECOND) as usize),
+            nodes => nodes,
+        };
+        strategy.limit = Some(limit);
+        strategy
+    }
+    
+    // Evaluate with C3 weighted otherwise than in the paper, e.g. by tuned
+    // weights loaded with `EvalWeights::load`
+    pub fn with_weights(mut self, weights: EvalWeights) -> Self {
+        self.evaluator = Box::new(C3Evaluator::new(weights));
+        self
+    }
+    
+    pub fn with_evaluator(mut self, evaluator: Box<dyn Evaluator>) -> Self {
+        self.base.name_evaluator(evaluator.as_ref());
+        self.evaluator = evaluator;
+        self
+    }
+    
+    // Whether the search has used up its budget
+    fn budget_spent(&self) -> bool {
+        match self.limit {
+            None => false,
+            Some(SearchLimit::Nodes(nodes)) => self.nodes >= nodes,
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(SearchLimit::Time(_)) => self.deadline.is_some_and(|deadline| Instant::now() >= deadline),
+            #[cfg(target_arch = "wasm32")]
+            Some(SearchLimit::Time(_)) => false,
+        }
+    }
+    
+    // The same inside negamax, where the clock is only read every so many
+    // nodes
+    fn out_of_budget(&self) -> bool {
+        match self.limit {
+            Some(SearchLimit::Time(_)) => self.nodes.is_multiple_of(256) && self.budget_spent(),
+            _ => self.budget_spent(),
+        }
+    }
+    
+    // Score of a win `plies` moves from the root: quicker wins score higher
+    fn win_in(plies: usize) -> f64 {
+        WIN_SCORE - plies as f64
+    }
+    
+    // The table keeps wins counted from the position stored rather than the
+    // root, so that they hold wherever the position turns up again
+    fn to_table(score: f64, ply: usize) -> f64 {
+        if score > WIN_BOUND {
+            score + ply as f64
+        } else if score < -WIN_BOUND {
+            score - ply as f64
+        } else {
+            score
+        }
+    }
+    
+    fn from_table(score: f64, ply: usize) -> f64 {
+        if score > WIN_BOUND {
+            score - ply as f64
+        } else if score < -WIN_BOUND {
+            score + ply as f64
+        } else {
+            score
         }
     }
     
+    // The score of a solved race `ply` moves from the root, for the side to
+    // move; none unless the walls are gone
+    fn race_score(&mut self, game: &Quoridor, ply: usize) -> Option<f64> {
+        let (result, plies) = self.races.result(game)?;
+        Some(match result.winner() {
+            Some(winner) if winner == game.active_player => Self::win_in(ply + plies),
+            Some(_) => -Self::win_in(ply + plies),
+            None => 0.0,
+        })
+    }
+    
+    // Scores the position for the side to move, as negamax expects
     pub fn evaluate(&self, game: &Quoridor) -> f64 {
+        self.evaluator.evaluate(game, game.active_player)
+    }
+    
+    // Search the position and report the move chosen, its score and the line
+    // expected to follow (as far as the transposition table remembers it),
+    // with how deep, how long and how many positions the search took. Unlike
+    // `choose_move`, never plays from the opening book.
+    pub fn search(&mut self, game: &Quoridor) -> SearchInfo {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = Instant::now();
+        
         let player = game.active_player;
-        let opponent = player.opponent();
+        let legal_pawn_moves = game.get_legal_moves(player);
+        let legal_wall_moves = if game.walls_available[&player] > 0 {
+            game.get_legal_walls(player)
+        } else {
+            Vec::new()
+        };
+        
+        // Check if any moves win immediately
+        if let Some(&mv) = legal_pawn_moves.iter().find(|&&mv| game.win_check(mv)) {
+            return SearchInfo {
+                best_move: Some(mv),
+                score: Self::win_in(1),
+                pv: vec![mv],
+                depth: 1,
+                nodes: 0,
+                #[cfg(not(target_arch = "wasm32"))]
+                time: start.elapsed(),
+                #[cfg(target_arch = "wasm32")]
+                time: Duration::ZERO,
+                table_hits: 0,
+            };
+        }
         
-        // Distance-based features
-        let player_distance = game.distance_to_goal(player) as f64;
-        let opponent_distance = game.distance_to_goal(opponent) as f64;
+        // With the walls gone the race is solved exactly, so play it out
+        // from the solution rather than searching
+        if let Some(race) = self.races.solve(game) {
+            let score = match race.result.winner() {
+                Some(winner) if winner == player => Self::win_in(race.plies()),
+                Some(_) => -Self::win_in(race.plies()),
+                None => 0.0,
+            };
+            return SearchInfo {
+                best_move: race.best_move(),
+                score,
+                depth: race.plies(),
+                pv: race.line,
+                nodes: 0,
+                #[cfg(not(target_arch = "wasm32"))]
+                time: start.elapsed(),
+                #[cfg(target_arch = "wasm32")]
+                time: Duration::ZERO,
+                table_hits: 0,
+            };
+        }
         
-        // Implementation of strategy C3 from the paper (f2 + f3 + f4)
+        // A single working copy that the whole search makes and unmakes moves on
+        let mut temp_game = game.clone();
+        self.table.new_search();
+        self.ordering.new_search();
         
-        // f2: Position difference feature (w2 = 0.6001)
-        let f2_position_diff = opponent_distance - player_distance;
+        // Every legal move is a candidate, best first as far as the ordering
+        // can tell, so that alpha-beta cuts the weak walls short
+        let mut candidates: Vec<Move> = legal_pawn_moves.into_iter().chain(legal_wall_moves).collect();
+        let table_move = self.table.probe(game.position_key()).and_then(|entry| entry.best_move);
+        self.ordering.order(game, &mut candidates, table_move, 0);
+        self.nodes = 0;
+        self.table_hits = 0;
+        self.stopped = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.deadline = match self.limit {
+                Some(SearchLimit::Time(time)) => Some(Instant::now() + time),
+                _ => None,
+            };
+        }
         
-        // f3: Max-player's moves to next column (w3 = 14.45)
-        let moves_next_player = game.moves_to_next_row(player);
-        let f3_attacking = if moves_next_player == 0 {
-            20.0 // Very high value for immediate progress
-        } else {
-            1.0 / (moves_next_player as f64) // Inverse of steps to next column
-        };
+        // Fixed depth is a single iteration
+        let first_depth = if self.limit.is_some() { 1 } else { self.depth };
         
-        // f4: Min-player's moves to next column (w4 = 6.52)
-        let moves_next_opponent = game.moves_to_next_row(opponent);
-        let f4_defensive = moves_next_opponent as f64;
+        let mut best: Option<(Move, f64)> = None;
+        let mut depth_reached = 0;
+        for depth in first_depth..=self.depth {
+            // Expect a score near the last iteration's, and search again with
+            // the full window if it falls outside
+            let (alpha, beta) = match best {
+                Some((_, score)) if score.abs() < WIN_BOUND => (score - ASPIRATION_WINDOW, score + ASPIRATION_WINDOW),
+                _ => (f64::NEG_INFINITY, f64::INFINITY),
+            };
+            let mut result = self.search_root(&mut temp_game, &candidates, depth, alpha, beta);
+            if result.is_some_and(|(_, score)| score <= alpha || score >= beta) {
+                result = self.search_root(&mut temp_game, &candidates, depth, f64::NEG_INFINITY, f64::INFINITY);
+            }
+            let Some((mv, score)) = result else {
+                break;
+            };
+            best = Some((mv, score));
+            depth_reached = depth;
+            
+            // Search this iteration's best move first in the next
+            let position = candidates.iter().position(|&other| other == mv).expect("best move is a candidate");
+            candidates[..=position].rotate_right(1);
+            
+            if candidates.len() == 1 || self.budget_spent() {
+                break;
+            }
+        }
         
-        // Using the weights from the research paper
-        const W2: f64 = 0.6001; // Position difference weight
-        const W3: f64 = 14.45;  // Max-player's moves to next column weight
-        const W4: f64 = 6.52;   // Min-player's moves to next column weight
+        // Out of budget before even one ply: fall back on the first candidate
+        let (best_move, score) = match best {
+            Some((mv, score)) => (Some(mv), score),
+            None => (candidates.first().copied(), 0.0),
+        };
+        let pv = match best {
+            Some((mv, _)) => self.principal_variation(&mut temp_game, mv, depth_reached),
+            None => best_move.into_iter().collect(),
+        };
         
-        // Combined evaluation: w2*f2 + w3*f3 + w4*f4
-        W2 * f2_position_diff + 
-        W3 * f3_attacking + 
-        W4 * f4_defensive
+        SearchInfo {
+            best_move,
+            score,
+            pv,
+            depth: depth_reached,
+            nodes: self.nodes,
+            #[cfg(not(target_arch = "wasm32"))]
+            time: start.elapsed(),
+            #[cfg(target_arch = "wasm32")]
+            time: Duration::ZERO,
+            table_hits: self.table_hits,
+        }
     }
     
-    pub fn minimax(&self, game: &Quoridor, depth: usize, mut alpha: f64, mut beta: f64, maximizing: bool) -> f64 {
-        // Check for game termination conditions
-        if depth == 0 || game.win_check(&game.last_move) {
-            return self.evaluate(game);
+    // The root move and the best replies stored in the table after it, up
+    // to `depth` moves. A stored move that is not legal means another
+    // position shared the slot, and ends the line.
+    fn principal_variation(&self, game: &mut Quoridor, first: Move, depth: usize) -> Vec<Move> {
+        let mut pv = vec![first];
+        game.make_move(first);
+        while pv.len() < depth && game.outcome().is_none() {
+            let Some(mv) = self.table.probe(game.position_key()).and_then(|entry| entry.best_move) else {
+                break;
+            };
+            if !game.legal_moves().contains(&mv) {
+                break;
+            }
+            game.make_move(mv);
+            pv.push(mv);
+        }
+        for _ in &pv {
+            game.unmake_move();
+        }
+        pv
+    }
+    
+    // Scores root moves to `depth` within the (alpha, beta) window and returns
+    // the best, the first of any tied. Each move after the first only has to
+    // be shown no better than the best so far, and a move at or above beta
+    // ends the search. None if the budget ran out part way.
+    fn search_root(&mut self, game: &mut Quoridor, moves: &[Move], depth: usize, mut alpha: f64, beta: f64) -> Option<(Move, f64)> {
+        let mut best: Option<(Move, f64)> = None;
+        for &mv in moves {
+            game.make_move(mv);
+            let score = -self.negamax(game, depth - 1, 1, -beta, -alpha);
+            game.unmake_move();
+            if self.stopped {
+                return None;
+            }
+            
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((mv, score));
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+    
+    // Scores the position for the side to move: each side's score is the
+    // negation of the best its opponent can do after its move. Searches in
+    // place: every move is made on `game` and unmade before returning.
+    // Results are kept in the transposition table, which can end the search
+    // of a position at once or at least say which move to try first. `ply`
+    // counts moves from the root, for the move ordering's killers and for
+    // scoring wins by how soon they come.
+    pub fn negamax(&mut self, game: &mut Quoridor, depth: usize, ply: usize, mut alpha: f64, mut beta: f64) -> f64 {
+        self.nodes += 1;
+        if self.stopped || self.out_of_budget() {
+            // Whatever is returned now is thrown away by the root
+            self.stopped = true;
+            return 0.0;
+        }
+        
+        if let Some(result) = game.outcome() {
+            return match result.winner() {
+                Some(winner) if winner == game.active_player => Self::win_in(ply),
+                Some(_) => -Self::win_in(ply),
+                None => 0.0,
+            };
+        }
+        
+        // With the walls gone the race is solved exactly, however much depth
+        // is left
+        if let Some(score) = self.race_score(game, ply) {
+            return score;
+        }
+        
+        let key = game.position_key();
+        let (original_alpha, original_beta) = (alpha, beta);
+        let mut table_move = None;
+        if let Some(entry) = self.table.probe(key) {
+            self.table_hits += 1;
+            table_move = entry.best_move;
+            if entry.depth >= depth {
+                let score = Self::from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+                if beta <= alpha {
+                    return score;
+                }
+            }
         }
         
-        let player = game.active_player;
-        let legal_pawn_moves = game.get_legal_moves(player);
-        let legal_wall_moves = if game.walls_available[&player] > 0 {
-            game.get_legal_walls(player)
-        } else {
-            Vec::new()
-        };
+        // Leaves are worth keeping too: the same position turns up at the
+        // horizon by many move orders, and evaluating it costs path searches
+        if depth == 0 {
+            let score = self.evaluate(game);
+            self.table.store(key, 0, score, Bound::Exact, None);
+            return score;
+        }
         
-        // First check pawn moves since they're typically better
-        let all_moves: Vec<String> = legal_pawn_moves.iter().cloned()
-            .chain(legal_wall_moves.iter().cloned())
-            .collect();
+        let mut all_moves = game.legal_moves();
         
         if all_moves.is_empty() {
             return self.evaluate(game);
         }
+        self.ordering.order(game, &mut all_moves, table_move, ply);
         
-        if maximizing {
-            let mut max_eval = f64::NEG_INFINITY;
-            
-            for move_str in &all_moves {
-                let mut temp_game = game.clone();
-                
-                // Apply move
-                if move_str.len() == 3 && (move_str.ends_with('h') || move_str.ends_with('v')) {
-                    temp_game.add_wall(move_str, false, false);
-                } else {
-                    temp_game.move_pawn(move_str, false);
-                }
-                
-                let eval = self.minimax(&temp_game, depth - 1, alpha, beta, false);
-                max_eval = max_eval.max(eval);
-                
-                // Update alpha for pruning
-                alpha = alpha.max(eval);
-                if beta <= alpha {
-                    break; // Beta cutoff
-                }
+        let mut best = f64::NEG_INFINITY;
+        let mut best_move = None;
+        for &mv in &all_moves {
+            game.make_move(mv);
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha);
+            game.unmake_move();
+            if self.stopped {
+                return 0.0;
             }
-            max_eval
-        } else {
-            let mut min_eval = f64::INFINITY;
             
-            for move_str in &all_moves {
-                let mut temp_game = game.clone();
-                
-                // Apply move
-                if move_str.len() == 3 && (move_str.ends_with('h') || move_str.ends_with('v')) {
-                    temp_game.add_wall(move_str, false, false);
-                } else {
-                    temp_game.move_pawn(move_str, false);
-                }
-                
-                let eval = self.minimax(&temp_game, depth - 1, alpha, beta, true);
-                min_eval = min_eval.min(eval);
-                
-                // Update beta for pruning
-                beta = beta.min(eval);
-                if beta <= alpha {
-                    break; // Alpha cutoff
-                }
+            if score > best {
+                best = score;
+                best_move = Some(mv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                self.ordering.record_cutoff(mv, ply, depth);
+                break; // The opponent will not allow this position
             }
-            min_eval
         }
+        
+        // Outside the window the score is only a bound on the true value
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= original_beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(key, depth, Self::to_table(best, ply), bound, best_move);
+        
+        best
     }
 }
 
@@ -1100,133 +2301,45 @@ impl Strategy for MinimaxStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
-        }
-        
-        let player = game.active_player;
-        let legal_pawn_moves = game.get_legal_moves(player);
-        let legal_wall_moves = if game.walls_available[&player] > 0 {
-            game.get_legal_walls(player)
-        } else {
-            Vec::new()
-        };
-        
-        let all_pawn_moves = legal_pawn_moves.clone();
-        
-        // Check if any moves win immediately
-        for move_str in &legal_pawn_moves {
-            if game.win_check(move_str) {
-                return Some(move_str.clone());
-            }
-        }
-        
-        let mut best_move = None;
-        let mut best_score = f64::NEG_INFINITY;
-        
-        // Use iterative deepening on wall moves to prioritize promising walls
-        // This helps when there are too many possible wall placements
-        let mut wall_moves_to_check = legal_wall_moves.clone();
-        
-        // If there are many wall moves, use a heuristic pre-filter 
-        // to identify promising walls that might block the opponent
-        if wall_moves_to_check.len() > 20 && !wall_moves_to_check.is_empty() {
-            let opponent = player.opponent();
-            let opponent_distance = game.distance_to_goal(opponent);
-            
-            // Score each wall by how much it increases opponent's path length
-            let mut wall_scores: Vec<(String, usize)> = Vec::new();
-            
-            for wall_move in &wall_moves_to_check {
-                let mut temp_game = game.clone();
-                if temp_game.add_wall(wall_move, false, false) {
-                    let new_distance = temp_game.distance_to_goal(opponent);
-                    let diff = new_distance.saturating_sub(opponent_distance);
-                    
-                    if diff > 0 {
-                        wall_scores.push((wall_move.clone(), diff));
-                    }
-                }
-            }
-            
-            // Sort walls by how much they increase opponent's path
-            wall_scores.sort_by(|a, b| b.1.cmp(&a.1));
-            
-            // Take the top 20 most promising walls
-            wall_moves_to_check = wall_scores.into_iter()
-                .take(20)
-                .map(|(wall, _)| wall)
-                .collect();
-        }
-        
-        // Evaluate pawn moves first (usually better than walls)
-        for move_str in &all_pawn_moves {
-            let mut temp_game = game.clone();
-            temp_game.move_pawn(move_str, false);
-            
-            let score = self.minimax(&temp_game, self.depth - 1, f64::NEG_INFINITY, f64::INFINITY, false);
-            
-            if score > best_score {
-                best_score = score;
-                best_move = Some(move_str.clone());
-            }
-        }
-        
-        // Evaluate wall moves
-        for move_str in &wall_moves_to_check {
-            let mut temp_game = game.clone();
-            temp_game.add_wall(move_str, false, false);
-            
-            let score = self.minimax(&temp_game, self.depth - 1, f64::NEG_INFINITY, f64::INFINITY, false);
-            
-            if score > best_score {
-                best_score = score;
-                best_move = Some(move_str.clone());
-            }
+        if let Some(mv) = self.base.try_opening_move(game) {
+            self.last_search = None;
+            return Some(mv);
         }
         
+        let info = self.search(game);
+        let best_move = info.best_move;
+        self.last_search = Some(info);
         best_move
     }
+    
+    fn search_info(&self) -> Option<SearchInfo> {
+        self.last_search.clone()
+    }
 }
 
 // Mirror Strategy
 pub struct MirrorStrategy {
     base: QuoridorStrategy,
     backup_strategy: Box<dyn Strategy>,
-    center: Option<(f64, f64)>,
 }
 
 impl MirrorStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>) -> Self {
         MirrorStrategy {
             base: QuoridorStrategy::new("Mirror", opening_name, opening_moves),
             backup_strategy: Box::new(AdaptiveStrategy::new("", Vec::new())),
-            center: None,
         }
     }
     
     pub fn calculate_mirrored_position(&self, game: &Quoridor, opponent: Player) -> Coord {
-        let center = match self.center {
-            Some(c) => c,
-            None => ((game.size - 1) as f64 / 2.0, (game.size - 1) as f64 / 2.0),
-        };
-        
-        let opponent_pos = game.pawn_positions[&opponent];
-        
-        // Calculate mirrored position
-        let mirrored_row = 2.0 * center.0 - opponent_pos.0 as f64;
-        let mirrored_col = 2.0 * center.1 - opponent_pos.1 as f64;
-        
-        // Ensure coordinates are within bounds
-        let row = (mirrored_row.round() as i32).max(0).min((game.size - 1) as i32) as usize;
-        let col = (mirrored_col.round() as i32).max(0).min((game.size - 1) as i32) as usize;
-        
-        (row, col)
+        // Opponent's square turned half way round the centre of the board
+        let (row, col) = game.pawn_positions[&opponent];
+        (game.size - 1 - row, game.size - 1 - col)
     }
     
-    pub fn find_best_move_toward(&self, game: &Quoridor, target_pos: Coord) -> Option<String> {
+    pub fn find_best_move_toward(&self, game: &Quoridor, target_pos: Coord) -> Option<Move> {
         let player = game.active_player;
         let current_pos = game.pawn_positions[&player];
         let legal_moves = game.get_legal_moves(player);
@@ -1238,11 +2351,8 @@ impl MirrorStrategy {
         let mut best_move = None;
         let mut best_distance = usize::MAX;
         
-        for move_str in &legal_moves {
-            let pos = match game.algebraic_to_coord(move_str) {
-                p => p,
-                _ => continue,
-            };
+        for &mv in &legal_moves {
+            let pos = game.move_coord(mv);
             
             // Calculate Manhattan distance to target
             let distance = abs_diff(pos.0, target_pos.0) + abs_diff(pos.1, target_pos.1);
@@ -1264,52 +2374,45 @@ impl MirrorStrategy {
             
             if total_score < best_distance {
                 best_distance = total_score;
-                best_move = Some(move_str.clone());
+                best_move = Some(mv);
             }
         }
         
         best_move
     }
     
-    pub fn mirror_opponent_walls(&self, game: &Quoridor, opponent: Player) -> Option<String> {
-        if game.walls_available[&game.active_player] <= 0 {
+    pub fn mirror_opponent_walls(&self, game: &Quoridor, _opponent: Player) -> Option<Move> {
+        if game.walls_available[&game.active_player] == 0 {
             return None;
         }
         
         // Get set of all walls on the board
         let mut all_walls = HashSet::new();
         for &wall in &game.hwall_positions {
-            all_walls.insert(format!("{}h", game.coord_to_algebraic(wall)));
+            all_walls.insert(Move::Wall(game.square_of(wall), Orientation::Horizontal));
         }
         for &wall in &game.vwall_positions {
-            all_walls.insert(format!("{}v", game.coord_to_algebraic(wall)));
+            all_walls.insert(Move::Wall(game.square_of(wall), Orientation::Vertical));
         }
         
         let legal_walls = game.get_legal_walls(game.active_player);
-        let center = match self.center {
-            Some(c) => c,
-            None => ((game.size - 1) as f64 / 2.0, (game.size - 1) as f64 / 2.0),
-        };
         
         // For each wall, calculate its mirrored position
         for wall in &all_walls {
-            let position = &wall[0..2];
-            let orientation = &wall[2..];
-            
-            let wall_pos = match game.algebraic_to_coord(position) {
-                p => p,
-                _ => continue,
+            let (wall_pos, orientation) = match *wall {
+                Move::Wall(coord, orientation) => (coord, orientation),
+                Move::Pawn(_) => continue,
             };
             
-            // Calculate mirrored wall position
-            let mirrored_row = 2.0 * center.0 - wall_pos.0 as f64;
-            let mirrored_col = 2.0 * center.1 - wall_pos.1 as f64;
-            
-            // Ensure coordinates are within bounds
-            let row = (mirrored_row.round() as i32).max(1).min((game.size - 1) as i32) as usize;
-            let col = (mirrored_col.round() as i32).max(0).min((game.size - 1) as i32) as usize;
-            
-            let mirrored_wall = format!("{}{}", game.coord_to_algebraic((row, col)), orientation);
+            // Turn the block of squares the wall divides half way round the
+            // centre; its anchor, the bottom-left square, comes from the
+            // opposite corner
+            let (rows, cols) = match orientation {
+                Orientation::Horizontal => (2, game.rules.wall_length),
+                Orientation::Vertical => (game.rules.wall_length, 2),
+            };
+            let mirrored = Square::new(game.size - cols - wall_pos.file, game.size + 2 - rows - wall_pos.rank);
+            let mirrored_wall = Move::Wall(mirrored, orientation);
             
             // If the mirrored wall is legal and not already placed
             if legal_walls.contains(&mirrored_wall) && !all_walls.contains(&mirrored_wall) {
@@ -1326,15 +2429,10 @@ impl Strategy for MirrorStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
-        }
-        
-        // Calculate board center if not already done
-        if self.center.is_none() {
-            self.center = Some(((game.size - 1) as f64 / 2.0, (game.size - 1) as f64 / 2.0));
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
         }
         
         let player = game.active_player;
@@ -1358,25 +2456,76 @@ impl Strategy for MirrorStrategy {
     }
 }
 
+// Perfect Strategy
+// Plays from a solved table: the quickest win, the slowest loss, or a move
+// that keeps a draw. It knows only the board and wall count the table was
+// solved for, under the standard rules (random starts included), and has no
+// move anywhere else.
+pub struct PerfectStrategy {
+    base: QuoridorStrategy,
+    table: Arc<SolutionTable>,
+}
+
+impl PerfectStrategy {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>, table: Arc<SolutionTable>) -> Self {
+        PerfectStrategy {
+            base: QuoridorStrategy::new("Perfect", opening_name, opening_moves),
+            table,
+        }
+    }
+}
+
+impl Strategy for PerfectStrategy {
+    fn name(&self) -> String {
+        self.base.name.clone()
+    }
+    
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
+        }
+        
+        let board = bitboard::BitboardGame::from_quoridor(game).ok()?;
+        self.table.best_move(&board)
+    }
+}
+
 // Utility functions
 pub fn abs_diff(a: usize, b: usize) -> usize {
-    if a > b { a - b } else { b - a }
+    a.abs_diff(b)
 }
 
 // Simulated Annealing Strategy
 pub struct SimulatedAnnealingStrategy {
     base: QuoridorStrategy,
     time_factor: f64,
+    evaluator: Box<dyn Evaluator>,
 }
 
 impl SimulatedAnnealingStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>, time_factor: f64) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>, time_factor: f64) -> Self {
+        // Weights from the paper: f2 (position difference), f3 (attacking
+        // feature/next row), f4 (defensive feature/wall difference)
+        let weights = EvalWeights::PAPER;
+        let evaluator = WeightedLinearEvaluator::new(vec![
+            (weights.position_difference, Feature::PathDifference),
+            (weights.attacking, Feature::NextRowProgress),
+            (weights.defensive, Feature::WallDifference),
+        ]);
+        
         SimulatedAnnealingStrategy {
             base: QuoridorStrategy::new(&format!("SimulatedAnnealing{}", time_factor), 
                                     opening_name, opening_moves),
             time_factor,
+            evaluator: Box::new(evaluator),
         }
     }
+    
+    pub fn with_evaluator(mut self, evaluator: Box<dyn Evaluator>) -> Self {
+        self.base.name_evaluator(evaluator.as_ref());
+        self.evaluator = evaluator;
+        self
+    }
 }
 
 impl Strategy for SimulatedAnnealingStrategy {
@@ -1384,14 +2533,14 @@ impl Strategy for SimulatedAnnealingStrategy {
         self.base.name.clone()
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // Try opening move
-        if let Some(move_str) = self.base.try_opening_move(game) {
-            return Some(move_str);
+        if let Some(mv) = self.base.try_opening_move(game) {
+            return Some(mv);
         }
         
         let player = game.active_player;
-        let opponent = player.opponent();
+        let opponent = game.leading_opponent(player);
         
         // Get all possible moves
         let possible_pawn_moves = game.get_legal_moves(player);
@@ -1406,15 +2555,11 @@ impl Strategy for SimulatedAnnealingStrategy {
         }
         
         // Check for immediate win
-        for move_str in &possible_pawn_moves {
-            if game.win_check(move_str) {
-                return Some(move_str.clone());
+        for &mv in &possible_pawn_moves {
+            if game.win_check(mv) {
+                return Some(mv);
             }
         }
-
-        let all_moves: Vec<String> = possible_pawn_moves.iter().cloned()
-            .chain(possible_wall_moves.iter().cloned())
-            .collect();
         
         let mut rng = rand::thread_rng();
         let mut time1 = 1;
@@ -1426,7 +2571,11 @@ impl Strategy for SimulatedAnnealingStrategy {
         
         // Following the paper's approach with nested annealing processes
         // Outer loop = Global annealing
-        let max_iterations = 120000; // Practical upper bound to prevent infinite loops
+        // Practical upper bound to prevent infinite loops, scaled by the time factor
+        let max_iterations = (120000.0 * self.time_factor) as usize;
+        
+        // Working copy that each 3-ply line is made and unmade on
+        let mut temp_game = game.clone();
         
         for _ in 0..max_iterations {
             time1 += 1;
@@ -1434,18 +2583,13 @@ impl Strategy for SimulatedAnnealingStrategy {
             // Randomly select a first move
             let wall_or_walk = rng.gen::<f64>();
             let first_move = if wall_or_walk > 0.5 && !possible_wall_moves.is_empty() {
-                possible_wall_moves[rng.gen_range(0..possible_wall_moves.len())].clone()
+                possible_wall_moves[rng.gen_range(0..possible_wall_moves.len())]
             } else {
-                possible_pawn_moves[rng.gen_range(0..possible_pawn_moves.len())].clone()
+                possible_pawn_moves[rng.gen_range(0..possible_pawn_moves.len())]
             };
             
             // Make the first move
-            let mut temp_game = game.clone();
-            if first_move.len() == 3 && (first_move.ends_with('h') || first_move.ends_with('v')) {
-                temp_game.add_wall(&first_move, false, false);
-            } else {
-                temp_game.move_pawn(&first_move, false);
-            }
+            temp_game.make_move(first_move);
             
             // Assume opponent makes best move for them
             let opponent_moves = temp_game.get_legal_moves(opponent);
@@ -1455,8 +2599,8 @@ impl Strategy for SimulatedAnnealingStrategy {
                 Vec::new()
             };
             
-            let all_opponent_moves: Vec<String> = opponent_moves.iter().cloned()
-                .chain(opponent_walls.iter().cloned())
+            let all_opponent_moves: Vec<Move> = opponent_moves.into_iter()
+                .chain(opponent_walls)
                 .collect();
                 
             if all_opponent_moves.is_empty() {
@@ -1465,31 +2609,23 @@ impl Strategy for SimulatedAnnealingStrategy {
             }
             
             // Find opponent's best move
-            let mut best_opponent_move = all_opponent_moves[0].clone();
+            let mut best_opponent_move = all_opponent_moves[0];
             let mut best_opponent_score = f64::MAX;
             
-            for m in &all_opponent_moves {
-                let mut sim_game = temp_game.clone();
-                if m.len() == 3 && (m.ends_with('h') || m.ends_with('v')) {
-                    sim_game.add_wall(m, false, false);
-                } else {
-                    sim_game.move_pawn(m, false);
-                }
+            for &m in &all_opponent_moves {
+                temp_game.make_move(m);
                 
                 // Lower score is better for opponent
-                let score = self.evaluate_position(&sim_game, player);
+                let score = self.evaluate_position(&temp_game, player);
+                temp_game.unmake_move();
                 if score < best_opponent_score {
                     best_opponent_score = score;
-                    best_opponent_move = m.clone();
+                    best_opponent_move = m;
                 }
             }
             
             // Make opponent's best move
-            if best_opponent_move.len() == 3 && (best_opponent_move.ends_with('h') || best_opponent_move.ends_with('v')) {
-                temp_game.add_wall(&best_opponent_move, false, false);
-            } else {
-                temp_game.move_pawn(&best_opponent_move, false);
-            }
+            temp_game.make_move(best_opponent_move);
             
             let h_cur_loc = self.evaluate_position(&temp_game, player);
             
@@ -1513,30 +2649,18 @@ impl Strategy for SimulatedAnnealingStrategy {
                     break;
                 }
                 
-                let all_second_moves: Vec<String> = second_player_moves.iter().cloned()
-                    .chain(second_player_walls.iter().cloned())
-                    .collect();
-                
                 let potential_move = if wall_or_walk2 > 0.5 && !second_player_walls.is_empty() {
-                    second_player_walls[rng.gen_range(0..second_player_walls.len())].clone()
+                    second_player_walls[rng.gen_range(0..second_player_walls.len())]
                 } else if !second_player_moves.is_empty() {
-                    second_player_moves[rng.gen_range(0..second_player_moves.len())].clone()
-                } else if !second_player_walls.is_empty() {
-                    second_player_walls[rng.gen_range(0..second_player_walls.len())].clone()
+                    second_player_moves[rng.gen_range(0..second_player_moves.len())]
                 } else {
-                    continue; // No moves available
+                    second_player_walls[rng.gen_range(0..second_player_walls.len())]
                 };
                 
-                // Make the potential second move
-                let mut sim_game = temp_game.clone();
-                if potential_move.len() == 3 && (potential_move.ends_with('h') || potential_move.ends_with('v')) {
-                    sim_game.add_wall(&potential_move, false, false);
-                } else {
-                    sim_game.move_pawn(&potential_move, false);
-                }
-                
-                // Evaluate the position after our second move
-                let h_next_loc = self.evaluate_position(&sim_game, player);
+                // Evaluate the position after the potential second move
+                temp_game.make_move(potential_move);
+                let h_next_loc = self.evaluate_position(&temp_game, player);
+                temp_game.unmake_move();
                 
                 // Calculate difference (flipping sign because we want to maximize our score)
                 let h_diff = h_next_loc - h_cur_loc;
@@ -1557,16 +2681,13 @@ impl Strategy for SimulatedAnnealingStrategy {
                 }
             }
             
+            // Back to the position after the first move, then to the root
+            temp_game.unmake_move();
+            let h_next_glob = third_move.map(|_| self.evaluate_position(&temp_game, player));
+            temp_game.unmake_move();
+            
             // Evaluate the whole 3-ply sequence to determine if this first move is good
-            if third_move.is_some() {
-                let mut sim_game = game.clone();
-                if first_move.len() == 3 && (first_move.ends_with('h') || first_move.ends_with('v')) {
-                    sim_game.add_wall(&first_move, false, false);
-                } else {
-                    sim_game.move_pawn(&first_move, false);
-                }
-                
-                let h_next_glob = self.evaluate_position(&sim_game, player);
+            if let Some(h_next_glob) = h_next_glob {
                 let h_cur_glob = self.evaluate_position(game, player);
                 
                 // Calculate global difference
@@ -1591,71 +2712,43 @@ impl Strategy for SimulatedAnnealingStrategy {
         
         // If we found a next move through the process, return it
         // Otherwise, just return a random legal move as fallback
-        if let Some(mv) = next_move {
-            return Some(mv);
-        } else {
-            // Fallback to a random move
-            let all_moves: Vec<String> = possible_pawn_moves.iter().cloned()
-                .chain(possible_wall_moves.iter().cloned())
+        next_move.or_else(|| {
+            let all_moves: Vec<Move> = possible_pawn_moves.iter()
+                .chain(possible_wall_moves.iter())
+                .copied()
                 .collect();
             
-            if !all_moves.is_empty() {
-                return Some(all_moves[rng.gen_range(0..all_moves.len())].clone());
-            } else {
-                return None;
-            }
-        }
+            all_moves.choose(&mut rng).copied()
+        })
     }
 }
 
 impl SimulatedAnnealingStrategy {
     // Evaluation function similar to the one in the paper
     pub fn evaluate_position(&self, game: &Quoridor, player: Player) -> f64 {
-        let opponent = player.opponent();
-        
-        // 1. Distance to goal for both players
-        let player_distance = game.distance_to_goal(player) as f64;
-        let opponent_distance = game.distance_to_goal(opponent) as f64;
-        let distance_diff = opponent_distance - player_distance;
-        
-        // 2. Wall resources
-        let player_walls = game.walls_available[&player] as f64;
-        let opponent_walls = game.walls_available[&opponent] as f64;
-        let wall_diff = player_walls - opponent_walls;
-        
-        // 3. Progress toward next row (key feature from the paper)
-        let moves_to_next = game.moves_to_next_row(player) as f64;
-        let next_row_factor = 1.0 / (moves_to_next + 0.1);  // Avoid division by zero
-        
-        // Combined evaluation with weights from the paper
-        // f2 (position difference) - weight 0.6001
-        // f3 (attacking feature/next row) - weight 14.45
-        // f4 (defensive feature/wall difference) - weight 6.52
-        0.6001 * distance_diff + 14.45 * next_row_factor + 6.52 * wall_diff
+        self.evaluator.evaluate(game, player)
     }
 }
 
 
 // MCTS Node structure to track game states
 struct MCTSNode {
-    move_str: String,               // Move that led to this state
+    mv: Option<Move>,               // Move that led to this state (None at the root)
     visits: usize,                  // Number of times this node has been visited
     wins: f64,                      // Number of wins from this node
     children: Vec<MCTSNode>,        // Child nodes
-    unexpanded_moves: Vec<String>,  // Unexplored moves from this position
-    player: Player,                 // Player who makes the move from this node
+    unexpanded_moves: Vec<Move>,    // Unexplored moves from this position
 }
 
 impl MCTSNode {
     // Create a new node for the MCTS tree
-    fn new(move_str: String, player: Player, unexpanded_moves: Vec<String>) -> Self {
+    fn new(mv: Option<Move>, unexpanded_moves: Vec<Move>) -> Self {
         MCTSNode {
-            move_str,
+            mv,
             visits: 0,
             wins: 0.0,
             children: Vec::new(),
             unexpanded_moves,
-            player,
         }
     }
     
@@ -1707,7 +2800,7 @@ impl MCTSNode {
 
 pub struct MCTSStrategy {
     opening_name: String,
-    opening_moves: Vec<String>,
+    opening_moves: Vec<Move>,
     move_counter: usize,
     simulation_limit: usize,
     exploration_param: f64,
@@ -1715,10 +2808,13 @@ pub struct MCTSStrategy {
     time_limit: Option<Duration>,
     #[cfg(target_arch = "wasm32")]
     time_limit_iterations: Option<usize>,
+    evaluator: Box<dyn Evaluator>, // Playouts race while it is not negative
+    evaluator_name: Option<String>, // Shown in the name when not the default
+    races: PawnRaceCache,
 }
 
 impl MCTSStrategy {
-    pub fn new(opening_name: &str, opening_moves: Vec<String>, simulation_limit: usize) -> Self {
+    pub fn new(opening_name: &str, opening_moves: Vec<Move>, simulation_limit: usize) -> Self {
         MCTSStrategy {
             opening_name: opening_name.to_string(),
             opening_moves,
@@ -1729,9 +2825,18 @@ impl MCTSStrategy {
             time_limit: None,
             #[cfg(target_arch = "wasm32")]
             time_limit_iterations: None,
+            evaluator: Box::new(PathDifferenceEvaluator),
+            evaluator_name: None,
+            races: PawnRaceCache::new(),
         }
     }
     
+    pub fn with_evaluator(mut self, evaluator: Box<dyn Evaluator>) -> Self {
+        self.evaluator_name = Some(evaluator.name());
+        self.evaluator = evaluator;
+        self
+    }
+    
     // Set a time limit for MCTS search
     #[cfg(not(target_arch = "wasm32"))]
     pub fn with_time_limit(mut self, seconds: f64) -> Self {
@@ -1749,7 +2854,7 @@ impl MCTSStrategy {
     }
     
     // Run MCTS algorithm to find the best move
-    fn run_mcts(&self, game: &Quoridor) -> String {
+    fn run_mcts(&mut self, game: &Quoridor) -> Move {
         let mut rng = rand::thread_rng();
         
         #[cfg(not(target_arch = "wasm32"))]
@@ -1759,28 +2864,22 @@ impl MCTSStrategy {
         let mut wasm_counter = WasmSafeInstant::now();
         
         // Get all possible moves from the current state
-        let legal_pawn_moves = game.get_legal_moves(game.active_player);
-        let legal_wall_moves = game.get_legal_walls(game.active_player);
-        
-        // Combine all possible moves
-        let all_moves: Vec<String> = legal_pawn_moves.into_iter()
-            .chain(legal_wall_moves.into_iter())
-            .collect();
+        let all_moves = game.legal_moves();
             
         // If there's only one move, return it immediately
         if all_moves.len() == 1 {
-            return all_moves[0].clone();
+            return all_moves[0];
         }
         
         // Create root node with all possible moves
-        let mut root = MCTSNode::new(
-            "root".to_string(),
-            game.active_player.opponent(), // The opponent made the last move to get to this state
-            all_moves.clone(),
-        );
+        let mut root = MCTSNode::new(None, all_moves.clone());
         
         let mut simulation_count = 0;
         
+        // Working copy of the game; every simulation is unwound back to the root
+        let mut current_game = game.clone();
+        let root_history_len = current_game.history.len();
+        
         // Continue until we hit our simulation limit or time limit
         while simulation_count < self.simulation_limit {
             // Check time limit if set
@@ -1798,9 +2897,6 @@ impl MCTSStrategy {
                 }
             }
             
-            // Clone the current game state for simulation
-            let mut current_game = game.clone();
-            
             // Phase 1: Selection - traverse the tree to a leaf node using UCT
             let mut current_node = &mut root;
             let mut path_to_leaf = Vec::new();
@@ -1819,47 +2915,29 @@ impl MCTSStrategy {
                 current_node = &mut current_node.children[best_child_idx];
                 
                 // Apply the move to the simulation game
-                let move_str = &current_node.move_str;
-                if move_str != "root" {
-                    if move_str.len() == 3 && (move_str.ends_with('h') || move_str.ends_with('v')) {
-                        current_game.add_wall(move_str, false, true);
-                    } else {
-                        current_game.move_pawn(move_str, true);
-                    }
+                if let Some(mv) = current_node.mv {
+                    current_game.make_move(mv);
                 }
             }
             
             // Phase 2: Expansion - expand the selected node with a random unexplored move
-            let mut selected_move = String::new();
-            
             if !current_node.unexpanded_moves.is_empty() {
                 // Randomly select an unexplored move
                 let random_idx = rng.gen_range(0..current_node.unexpanded_moves.len());
-                selected_move = current_node.unexpanded_moves.remove(random_idx);
-                
-                // Create a new child node for this move
-                let next_player = current_game.active_player;
+                let selected_move = current_node.unexpanded_moves.remove(random_idx);
                 
                 // Apply the move to the simulation
-                if selected_move.len() == 3 && (selected_move.ends_with('h') || selected_move.ends_with('v')) {
-                    current_game.add_wall(&selected_move, false, true);
-                } else {
-                    current_game.move_pawn(&selected_move, true);
-                }
+                current_game.make_move(selected_move);
                 
-                // Get the next possible moves for the new node
-                let next_legal_pawn_moves = current_game.get_legal_moves(current_game.active_player);
-                let next_legal_wall_moves = current_game.get_legal_walls(current_game.active_player);
-                let next_moves: Vec<String> = next_legal_pawn_moves.into_iter()
-                    .chain(next_legal_wall_moves.into_iter())
-                    .collect();
+                // Get the next possible moves for the new node; a finished game has none
+                let next_moves = if current_game.outcome().is_some() {
+                    Vec::new()
+                } else {
+                    current_game.legal_moves()
+                };
                 
                 // Create and add the new child node
-                let new_node = MCTSNode::new(
-                    selected_move.clone(),
-                    next_player,
-                    next_moves,
-                );
+                let new_node = MCTSNode::new(Some(selected_move), next_moves);
                 
                 current_node.children.push(new_node);
                 current_node = current_node.children.last_mut().unwrap();
@@ -1869,6 +2947,11 @@ impl MCTSStrategy {
             // Phase 3: Simulation - play out the game randomly until a terminal state
             let winner = self.simulate_game(&mut current_game);
             
+            // Unwind the selection, expansion and playout moves
+            while current_game.history.len() > root_history_len {
+                current_game.unmake_move();
+            }
+            
             // Phase 4: Backpropagation - update win/visit counts up the tree
             let original_player = game.active_player;
             
@@ -1893,48 +2976,38 @@ impl MCTSStrategy {
         // Choose the best child of the root based on visit count
         if root.children.is_empty() {
             // If no simulations were completed, choose a random move
-            all_moves[rng.gen_range(0..all_moves.len())].clone()
+            all_moves[rng.gen_range(0..all_moves.len())]
         } else {
             let best_child_idx = root.best_child();
-            root.children[best_child_idx].move_str.clone()
+            root.children[best_child_idx].mv.expect("child nodes always carry a move")
         }
     }
     
     // Simulate a game from the current state to completion using the heuristic described in the paper
-    fn simulate_game(&self, game: &mut Quoridor) -> Option<Player> {
+    fn simulate_game(&mut self, game: &mut Quoridor) -> Option<Player> {
+        // A race without walls needs no playout: its result is known
+        if let Some((result, _)) = self.races.result(game) {
+            return result.winner();
+        }
+        
         let mut rng = rand::thread_rng();
-        let mut move_count = 0;
-        let max_moves = 200; // Prevent infinite games
-        
-        // Continue until the game ends or max moves reached
-        while move_count < max_moves {
-            // Check if either player has won
-            let player1_pos = game.pawn_positions.get(&Player::Player1).unwrap();
-            let player2_pos = game.pawn_positions.get(&Player::Player2).unwrap();
-            
-            // Check player 1 win (reached row 0)
-            if player1_pos.0 == 0 {
-                return Some(Player::Player1);
-            }
-            
-            // Check player 2 win (reached bottom row)
-            if player2_pos.0 == game.size - 1 {
-                return Some(Player::Player2);
+        
+        // Continue until the game ends, at the latest at the rules' move limit
+        loop {
+            if let Some(result) = game.outcome() {
+                return result.winner();
             }
             
             // Use the heuristic described in the paper (page 23)
             let current_player = game.active_player;
-            let opponent = current_player.opponent();
-            
-            // Calculate shortest path distances
             let player_distance = game.distance_to_goal(current_player);
-            let opponent_distance = game.distance_to_goal(opponent);
             
             // Following the paper's heuristic:
             // "The heuristic decision used in the simulation phase is basically
             // based on comparing if the shortest path until the goal of the current player
-            // is less than the opponent's one."
-            if player_distance <= opponent_distance || game.walls_available[&current_player] == 0 {
+            // is less than the opponent's one." The evaluator makes the comparison,
+            // by default on path lengths alone.
+            if self.evaluator.evaluate(game, current_player) >= 0.0 || game.walls_available[&current_player] == 0 {
                 // Follow shortest path - pawn movement only
                 let pawn_moves = game.get_legal_moves(current_player);
                 
@@ -1946,159 +3019,173 @@ impl MCTSStrategy {
                 let mut best_moves = Vec::new();
                 let mut best_distance = player_distance;
                 
-                for move_str in &pawn_moves {
-                    let mut temp_game = game.clone();
-                    temp_game.move_pawn(move_str, true);
-                    let new_distance = temp_game.distance_to_goal(current_player);
+                for &mv in &pawn_moves {
+                    game.make_move(mv);
+                    let new_distance = game.distance_to_goal(current_player);
+                    game.unmake_move();
                     
                     if new_distance < best_distance {
                         best_moves.clear();
-                        best_moves.push(move_str);
+                        best_moves.push(mv);
                         best_distance = new_distance;
                     } else if new_distance == best_distance {
-                        best_moves.push(move_str);
+                        best_moves.push(mv);
                     }
                 }
                 
                 // If no good move found, use any legal pawn move
                 if best_moves.is_empty() {
-                    best_moves = pawn_moves.iter().collect();
+                    best_moves = pawn_moves;
                 }
                 
                 // Choose randomly from best moves
                 let move_idx = rng.gen_range(0..best_moves.len());
-                let selected_move = best_moves[move_idx];
-                game.move_pawn(selected_move, true);
+                game.make_move(best_moves[move_idx]);
             } else {
                 // Consider all possible moves (including walls)
-                let pawn_moves = game.get_legal_moves(current_player);
-                let wall_moves = game.get_legal_walls(current_player);
-                
-                let mut all_moves = Vec::new();
-                all_moves.extend(pawn_moves);
-                all_moves.extend(wall_moves);
+                let all_moves = game.legal_moves();
                 
                 if all_moves.is_empty() {
                     return None; // No moves available
                 }
                 
-                // Choose a random move from all possible moves
+                // Choose a random move from all possible moves and apply it
                 let move_idx = rng.gen_range(0..all_moves.len());
-                let selected_move = &all_moves[move_idx];
-                
-                // Apply the move
-                if selected_move.len() == 3 && (selected_move.ends_with('h') || selected_move.ends_with('v')) {
-                    game.add_wall(selected_move, false, true);
-                } else {
-                    game.move_pawn(selected_move, true);
-                }
+                game.make_move(all_moves[move_idx]);
             }
-            
-            move_count += 1;
         }
-        
-        // If maximum moves reached, return none (draw)
-        None
     }
 }
 
 impl Strategy for MCTSStrategy {
     fn name(&self) -> String {
-        format!("MCTS-{}", self.simulation_limit)
+        let evaluator = self.evaluator_name.as_ref().map(|name| format!("@{}", name)).unwrap_or_default();
+        if self.opening_moves.is_empty() {
+            format!("MCTS-{}{}", self.simulation_limit, evaluator)
+        } else {
+            format!("MCTS-{}{}-{}", self.simulation_limit, evaluator, self.opening_name)
+        }
     }
     
-    fn choose_move(&mut self, game: &Quoridor) -> Option<String> {
+    fn choose_move(&mut self, game: &Quoridor) -> Option<Move> {
         // First, try to use an opening move if available
         if self.move_counter < self.opening_moves.len() {
-            let move_str = self.opening_moves[self.move_counter].clone();
+            let mv = self.opening_moves[self.move_counter];
             self.move_counter += 1;
             
             // Verify that the opening move is legal
-            let legal_pawn_moves = game.get_legal_moves(game.active_player);
-            let legal_wall_moves = game.get_legal_walls(game.active_player);
-            
-            let all_legal_moves: Vec<String> = legal_pawn_moves.iter().cloned()
-                .chain(legal_wall_moves.iter().cloned())
-                .collect();
-            
-            if all_legal_moves.contains(&move_str) {
-                return Some(move_str);
+            if game.legal_moves().contains(&mv) {
+                return Some(mv);
             }
         }
         
+        // A race without walls is solved exactly instead of simulated
+        if let Some(mv) = self.races.solve(game).and_then(|race| race.best_move()) {
+            return Some(mv);
+        }
+        
         // If no opening move or it's invalid, run MCTS
         Some(self.run_mcts(game))
     }
 }
 
 // Opening moves
-pub fn get_opening_moves(opening_name: &str, player: Player) -> Vec<String> {
-    match (opening_name, player) {
-        ("No Opening", Player::Player1) => vec!["e2".to_string()],
-        ("No Opening", Player::Player2) => vec!["e8".to_string()],
-
-        ("Sidewall Opening", Player::Player1) => vec!["c3h".to_string(), "f3h".to_string()],
-        ("Sidewall Opening", Player::Player2) => vec!["a3h".to_string(), "h3h".to_string()],
-
-        ("Shiller Opening", Player::Player1) => vec!["e2".to_string(), "e3".to_string(), "e4".to_string(), "c3v".to_string()],
-        ("Shiller Opening", Player::Player2) => vec!["e8".to_string(), "e7".to_string(), "e6".to_string()],
-
-        ("Stonewall", Player::Player1) => vec!["e2".to_string(), "e3".to_string(), "d2h".to_string()],
-        ("Stonewall", Player::Player2) => vec!["e8".to_string(), "e7".to_string()],
-
-        ("Ala Opening", Player::Player1) => vec![
-            "e2".to_string(), "e3".to_string(), "e4".to_string(), 
-            "d5h".to_string(), "f5h".to_string(), "c4v".to_string(), "g4v".to_string()
+// Opening lines are 9x9 theory. On other boards every move is shifted so the
+// line keeps its shape around the player's own starting square, and the line
+// stops at the first move that no longer fits on the board.
+pub fn get_opening_moves(opening_name: &str, player: Player, size: usize) -> Vec<Move> {
+    let moves: &[&str] = match (opening_name, player) {
+        ("No Opening", Player::Player1) => &["e2"],
+        ("No Opening", Player::Player2) => &["e8"],
+
+        ("Sidewall Opening", Player::Player1) => &["c3h", "f3h"],
+        ("Sidewall Opening", Player::Player2) => &["a3h", "h3h"],
+
+        ("Shiller Opening", Player::Player1) => &["e2", "e3", "e4", "c3v"],
+        ("Shiller Opening", Player::Player2) => &["e8", "e7", "e6"],
+
+        ("Stonewall", Player::Player1) => &["e2", "e3", "d2h"],
+        ("Stonewall", Player::Player2) => &["e8", "e7"],
+
+        ("Ala Opening", Player::Player1) => &[
+            "e2", "e3", "e4", 
+            "d5h", "f5h", "c4v", "g4v"
         ],
-        ("Ala Opening", Player::Player2) => vec!["e8".to_string(), "e7".to_string(), "e6".to_string()],
+        ("Ala Opening", Player::Player2) => &["e8", "e7", "e6"],
 
-        ("Standard Opening", Player::Player1) => vec!["e2".to_string(), "e3".to_string(), "e4".to_string(), "e3v".to_string()],
-        ("Standard Opening", Player::Player2) => vec!["e8".to_string(), "e7".to_string(), "e6".to_string(), "e6v".to_string()],
+        ("Standard Opening", Player::Player1) => &["e2", "e3", "e4", "e3v"],
+        ("Standard Opening", Player::Player2) => &["e8", "e7", "e6", "e6v"],
 
-        ("Standard Opening (Symmetrical)", Player::Player1) => vec!["e2".to_string(), "e3".to_string(), "e4".to_string(), "e3v".to_string()],
-        ("Standard Opening (Symmetrical)", Player::Player2) => vec!["e8".to_string(), "e7".to_string(), "e6".to_string(), "d6v".to_string()],
+        ("Standard Opening (Symmetrical)", Player::Player1) => &["e2", "e3", "e4", "e3v"],
+        ("Standard Opening (Symmetrical)", Player::Player2) => &["e8", "e7", "e6", "d6v"],
 
-        ("Rush Variation", Player::Player1) => vec![
-            "e2".to_string(), "e3".to_string(), "e4".to_string(), 
-            "d5v".to_string(), "e4h".to_string(), "g4h".to_string(), "h5v".to_string()
+        ("Rush Variation", Player::Player1) => &[
+            "e2", "e3", "e4", 
+            "d5v", "e4h", "g4h", "h5v"
         ],
-        ("Rush Variation", Player::Player2) => vec![
-            "e8".to_string(), "e7".to_string(), "e6".to_string(), 
-            "e6h".to_string(), "f6".to_string(), "f5".to_string(), "g5".to_string()
+        ("Rush Variation", Player::Player2) => &[
+            "e8", "e7", "e6", 
+            "e6h", "f6", "f5", "g5"
         ],
 
-        ("Gap Opening", Player::Player1) => vec!["e2".to_string(), "e3".to_string(), "e4".to_string()],
-        ("Gap Opening", Player::Player2) => vec!["e8".to_string(), "e7".to_string(), "e6".to_string()],
+        ("Gap Opening", Player::Player1) => &["e2", "e3", "e4"],
+        ("Gap Opening", Player::Player2) => &["e8", "e7", "e6"],
 
-        ("Gap Opening (Mainline)", Player::Player1) => vec!["e2".to_string(), "e3".to_string(), "e4".to_string()],
-        ("Gap Opening (Mainline)", Player::Player2) => vec!["e8".to_string(), "e7".to_string(), "e6".to_string(), "g6h".to_string()],
+        ("Gap Opening (Mainline)", Player::Player1) => &["e2", "e3", "e4"],
+        ("Gap Opening (Mainline)", Player::Player2) => &["e8", "e7", "e6", "g6h"],
 
-        ("Anti-Gap", Player::Player1) => vec!["e2".to_string(), "e3".to_string(), "e4".to_string()],
-        ("Anti-Gap", Player::Player2) => vec!["e8".to_string(), "e7".to_string(), "e6".to_string(), "b3h".to_string()],
+        ("Anti-Gap", Player::Player1) => &["e2", "e3", "e4"],
+        ("Anti-Gap", Player::Player2) => &["e8", "e7", "e6", "b3h"],
 
-        ("Sidewall", Player::Player1) => vec!["e2".to_string(), "d7v".to_string()],
-        ("Sidewall", Player::Player2) => vec!["e8".to_string()],
+        ("Sidewall", Player::Player1) => &["e2", "d7v"],
+        ("Sidewall", Player::Player2) => &["e8"],
 
-        ("Sidewall (Proper Counter)", Player::Player1) => vec!["e2".to_string(), "d7v".to_string()],
-        ("Sidewall (Proper Counter)", Player::Player2) => vec!["e8".to_string(), "c7h".to_string()],
+        ("Sidewall (Proper Counter)", Player::Player1) => &["e2", "d7v"],
+        ("Sidewall (Proper Counter)", Player::Player2) => &["e8", "c7h"],
 
-        ("Quick Box Variation", Player::Player1) => vec!["e2".to_string()],
-        ("Quick Box Variation", Player::Player2) => vec!["e8".to_string(), "d1h".to_string()],
+        ("Quick Box Variation", Player::Player1) => &["e2"],
+        ("Quick Box Variation", Player::Player2) => &["e8", "d1h"],
 
-        ("Shatranj Opening", Player::Player1) => vec!["d1v".to_string()],
-        ("Shatranj Opening", Player::Player2) => vec![],
+        ("Shatranj Opening", Player::Player1) => &["d1v"],
+        ("Shatranj Opening", Player::Player2) => &[],
 
-        ("Lee Inversion", Player::Player1) => vec!["e1v".to_string()],
-        ("Lee Inversion", Player::Player2) => vec![],
+        ("Lee Inversion", Player::Player1) => &["e1v"],
+        ("Lee Inversion", Player::Player2) => &[],
 
-        _ => Vec::new(),
-    }
+        _ => &[],
+    };
+    
+    let (rank_shift, file_shift) = match player {
+        Player::Player1 => (0, (size as isize - 1) / 2 - 4),
+        Player::Player2 => (size as isize - 9, size as isize / 2 - 4),
+        // Openings are two-player lines
+        Player::Player3 | Player::Player4 => return Vec::new(),
+    };
+    
+    moves.iter()
+        .map(|m| m.parse::<Move>().expect("opening moves are valid notation"))
+        .map_while(|mv| {
+            let square = mv.square();
+            let (rank, file) = (square.rank as isize + rank_shift, square.file as isize + file_shift);
+            // Walls are anchored on a square with room for the wall above and to its right
+            let limit = if mv.is_wall() { size as isize - 1 } else { size as isize };
+            if rank < 1 || file < 0 || rank > limit || file >= limit {
+                return None;
+            }
+            let square = Square::new(file as usize, rank as usize);
+            Some(match mv {
+                Move::Pawn(_) => Move::Pawn(square),
+                Move::Wall(_, orientation) => Move::Wall(square, orientation),
+            })
+        })
+        .collect()
 }
 
 
 // Tournament pub structure
 #[derive(Debug, Clone)]
 pub struct TournamentResult {
+    rules: RuleSet,
     strategy1: String,
     strategy2: String,
     opening: String,
@@ -2111,60 +3198,104 @@ pub struct Tournament {
     board_size: usize,
     walls: usize,
     games_per_match: usize,
+    rules: RuleSet,
+    weights: EvalWeights, // For the Minimax strategies
+    solution: Option<Arc<SolutionTable>>, // For the Perfect strategy
     results: Vec<TournamentResult>,
 }
 
 impl Tournament {
-    pub fn new(board_size: usize, walls: usize, games_per_match: usize) -> Self {
+    pub fn new(board_size: usize, walls: usize, games_per_match: usize, rules: RuleSet) -> Self {
         Tournament {
             board_size,
             walls,
             games_per_match,
+            rules,
+            weights: EvalWeights::PAPER,
+            solution: None,
             results: Vec::new(),
         }
     }
     
-    pub fn create_strategy(&self, strategy_name: &str, opening_name: &str, player: Player) -> Box<dyn Strategy> {
-        let opening_moves = get_opening_moves(opening_name, player);
+    pub fn with_weights(mut self, weights: EvalWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+    
+    // A solved table for this board lets the Perfect strategy take part
+    pub fn with_solution(mut self, solution: Arc<SolutionTable>) -> Self {
+        self.solution = Some(solution);
+        self
+    }
+    
+    // An unknown evaluator, or Perfect with no table loaded, is an error; an
+    // unknown strategy name plays at random
+    pub fn create_strategy(&self, strategy_name: &str, opening_name: &str, player: Player) -> Result<Box<dyn Strategy>, QuoridorError> {
+        let opening_moves = get_opening_moves(opening_name, player, self.board_size);
+        
+        // An evaluator named after '@' (e.g., Minimax2@PathDifference) replaces
+        // the strategy's own
+        let (strategy_name, evaluator) = match strategy_name.split_once('@') {
+            Some((name, evaluator)) => (name, Some(evaluation::parse_evaluator(evaluator)?)),
+            None => (strategy_name, None),
+        };
         
-        match strategy_name {
+        let strategy: Box<dyn Strategy> = match strategy_name {
             "Random" => Box::new(RandomStrategy::new(opening_name, opening_moves)),
             "ShortestPath" => Box::new(ShortestPathStrategy::new(opening_name, opening_moves)),
             "Defensive" => Box::new(DefensiveStrategy::new(opening_name, opening_moves, 0.7)),
             "Balanced" => Box::new(BalancedStrategy::new(opening_name, opening_moves, 0.5)),
-            "Adaptive" => Box::new(AdaptiveStrategy::new(opening_name, opening_moves)),
+            "Adaptive" => Box::new(evaluator.into_iter().fold(AdaptiveStrategy::new(opening_name, opening_moves), AdaptiveStrategy::with_evaluator)),
             "Mirror" => Box::new(MirrorStrategy::new(opening_name, opening_moves)),
+            "Perfect" => match &self.solution {
+                Some(table) => Box::new(PerfectStrategy::new(opening_name, opening_moves, Arc::clone(table))),
+                None => return Err(QuoridorError::NoSolution),
+            },
             s if s.starts_with("SimulatedAnnealing") => {
                 let factor = s[18..].parse::<f64>().unwrap_or(1.0);
-                Box::new(SimulatedAnnealingStrategy::new(opening_name, opening_moves, factor))
+                let strategy = SimulatedAnnealingStrategy::new(opening_name, opening_moves, factor);
+                Box::new(evaluator.into_iter().fold(strategy, SimulatedAnnealingStrategy::with_evaluator))
             },
             s if s.starts_with("Minimax") => {
                 let depth = s[7..].parse::<usize>().unwrap_or(1);
-                Box::new(MinimaxStrategy::new(opening_name, opening_moves, depth))
+                let strategy = MinimaxStrategy::new(opening_name, opening_moves, depth, DEFAULT_TABLE_MB).with_weights(self.weights);
+                Box::new(evaluator.into_iter().fold(strategy, MinimaxStrategy::with_evaluator))
+            },
+            s if s.starts_with("IDMinimax") => {
+                // Search budget from the name (e.g., IDMinimax2s, IDMinimax500ms, IDMinimax100kn)
+                let limit = s[9..].parse::<SearchLimit>().unwrap_or(SearchLimit::Time(Duration::from_secs(1)));
+                let strategy = MinimaxStrategy::iterative(opening_name, opening_moves, limit, DEFAULT_TABLE_MB).with_weights(self.weights);
+                Box::new(evaluator.into_iter().fold(strategy, MinimaxStrategy::with_evaluator))
             },
             s if s.starts_with("MCTS") => {
                 // Parse simulation count from strategy name (e.g., MCTS60k -> 60000 simulations)
-                if let Ok(simulations) = s[4..].replace("k", "000").parse::<usize>() {
-                    Box::new(MCTSStrategy::new(opening_name, opening_moves, simulations))
-                } else {
-                    // Default to 10k simulations if parsing fails
-                    Box::new(MCTSStrategy::new(opening_name, opening_moves, 10000))
-                }
+                // Default to 10k simulations if parsing fails
+                let simulations = s[4..].replace("k", "000").parse::<usize>().unwrap_or(10000);
+                let strategy = MCTSStrategy::new(opening_name, opening_moves, simulations);
+                Box::new(evaluator.into_iter().fold(strategy, MCTSStrategy::with_evaluator))
             },
             _ => Box::new(RandomStrategy::new(opening_name, opening_moves)), // Default
-        }
+        };
+        Ok(strategy)
     }
 
     pub fn run_debug_match(&mut self, strategy1_name: &str, strategy2_name: &str, opening_name: &str) {
         println!("\n=== DEBUG MATCH: {} vs {} with {} ===", 
                 strategy1_name, strategy2_name, opening_name);
         
-        let mut first_strategy = self.create_strategy(strategy1_name, opening_name, Player::Player1);
-        let mut second_strategy = self.create_strategy(strategy2_name, opening_name, Player::Player2);
+        let strategies = self.create_strategy(strategy1_name, opening_name, Player::Player1)
+            .and_then(|first| Ok((first, self.create_strategy(strategy2_name, opening_name, Player::Player2)?)));
+        let (mut first_strategy, mut second_strategy) = match strategies {
+            Ok(strategies) => strategies,
+            Err(e) => {
+                eprintln!("Cannot play {} vs {}: {}", strategy1_name, strategy2_name, e);
+                return;
+            }
+        };
         
         // Setup the game
-        let mut game = Quoridor::new(self.board_size, self.walls, None);
-        let mut move_count = 0;
+        let mut game = Quoridor::new(self.board_size, self.walls, self.rules, None)
+            .expect("tournament board configuration is valid");
         
         // Play the game
         loop {
@@ -2175,7 +3306,7 @@ impl Tournament {
                 &mut second_strategy 
             };
             
-            println!("Turn {}: {}'s move", move_count, current_player.name());
+            println!("Turn {}: {}'s move", game.history.len(), current_player.name());
             let move_result = current_strategy.choose_move(&game);
             
             if move_result.is_none() {
@@ -2183,33 +3314,20 @@ impl Tournament {
                 break;
             }
             
-            let move_str = move_result.unwrap();
-            println!("Move chosen: {}", move_str);
-            
-            // Check for win
-            if game.win_check(&move_str) {
-                println!("{} wins with move {}", current_player.name(), move_str);
-                break;
+            let mv = move_result.unwrap();
+            println!("Move chosen: {}", mv);
+            if let Some(info) = current_strategy.search_info() {
+                println!("Search: {}", info);
             }
             
             // Apply the move
-            let move_success = if move_str.len() == 3 && 
-                            (move_str.ends_with('h') || move_str.ends_with('v')) {
-                game.add_wall(&move_str, false, true)
-            } else {
-                game.move_pawn(&move_str, true)
-            };
-            
-            if !move_success {
-                println!("MOVE FAILED: {}", move_str);
+            if let Err(e) = game.apply(mv) {
+                println!("MOVE FAILED: {} ({})", mv, e);
                 break;
             }
             
-            move_count += 1;
-            
-            // Maximum moves safeguard
-            if move_count > 50 {
-                println!("Game drawn after {} moves", move_count);
+            if let Some(result) = game.outcome() {
+                println!("Game over after move {}: {}", mv, result);
                 break;
             }
         }
@@ -2236,12 +3354,19 @@ impl Tournament {
                 };
             
             // Create strategies
-            let mut first_strategy = self.create_strategy(first_strategy_type, opening_name, first_player);
-            let mut second_strategy = self.create_strategy(second_strategy_type, opening_name, second_player);
+            let strategies = self.create_strategy(first_strategy_type, opening_name, first_player)
+                .and_then(|first| Ok((first, self.create_strategy(second_strategy_type, opening_name, second_player)?)));
+            let (mut first_strategy, mut second_strategy) = match strategies {
+                Ok(strategies) => strategies,
+                Err(e) => {
+                    eprintln!("Cannot play {} vs {}: {}", first_strategy_type, second_strategy_type, e);
+                    return;
+                }
+            };
             
             // Setup the game
-            let mut game = Quoridor::new(self.board_size, self.walls, None);
-            let mut move_count = 0;
+            let mut game = Quoridor::new(self.board_size, self.walls, self.rules, None)
+                .expect("tournament board configuration is valid");
             
             // Play the game
             loop {
@@ -2256,50 +3381,45 @@ impl Tournament {
                 
                 if move_result.is_none() {
                     // No valid moves, current player loses
-                    if current_player == first_player {
-                        if first_strategy_type == strategy1_name { s2_wins += 1; } else { s1_wins += 1; }
-                    } else {
-                        if second_strategy_type == strategy1_name { s2_wins += 1; } else { s1_wins += 1; }
-                    }
+                    let loser_type = if current_player == first_player { first_strategy_type } else { second_strategy_type };
+                    if loser_type == strategy1_name { s2_wins += 1; } else { s1_wins += 1; }
                     break;
                 }
                 
-                let move_str = move_result.unwrap();
-                
-                // Check for win
-                if game.win_check(&move_str) {
-                    if current_player == first_player {
-                        if first_strategy_type == strategy1_name { s1_wins += 1; } else { s2_wins += 1; }
-                    } else {
-                        if second_strategy_type == strategy1_name { s1_wins += 1; } else { s2_wins += 1; }
+                let mv = move_result.unwrap();
+                if display {
+                    if let Some(info) = current_strategy.search_info() {
+                        println!("{} played {}: {}", current_strategy.name(), mv, info);
                     }
-                    move_count += 1;
-                    break;
                 }
                 
-                // Apply the move
-                let move_success = if move_str.len() == 3 && 
-                                (move_str.ends_with('h') || move_str.ends_with('v')) {
-                    game.add_wall(&move_str, false, true)
-                } else {
-                    game.move_pawn(&move_str, true)
-                };
-                
-                if !move_success && display {
-                    println!("MOVE FAILED: {}", move_str);
+                // Apply the move; an illegal one forfeits like having no move
+                if let Err(e) = game.apply(mv) {
+                    if display {
+                        println!("MOVE FAILED: {} ({})", mv, e);
+                    }
+                    let loser_type = if current_player == first_player { first_strategy_type } else { second_strategy_type };
+                    if loser_type == strategy1_name { s2_wins += 1; } else { s1_wins += 1; }
+                    break;
                 }
                 
-                move_count += 1;
-                
-                // Maximum moves safeguard
-                if move_count > 150 {
-                    draws += 1;
-                    break;
+                match game.outcome() {
+                    Some(GameResult::Win(winner)) => {
+                        let winner_type = if winner == first_player { first_strategy_type } else { second_strategy_type };
+                        if winner_type == strategy1_name { s1_wins += 1; } else { s2_wins += 1; }
+                        break;
+                    },
+                    Some(GameResult::Draw(_)) => {
+                        draws += 1;
+                        break;
+                    },
+                    None => {},
                 }
             }
         }
         
         self.results.push(TournamentResult {
+            rules: self.rules,
             strategy1: strategy1_name.to_string(),
             strategy2: strategy2_name.to_string(),
             opening: opening_name.to_string(),
@@ -2318,8 +3438,8 @@ impl Tournament {
             "SimulatedAnnealing1.0",
             "SimulatedAnnealing1.5",
             "SimulatedAnnealing2.0",
-            "ProgressiveDeepening2",
-            "ProgressiveDeepening3"
+            "IDMinimax1s",
+            "IDMinimax2s"
         ];
         
         let opening_names = vec![
@@ -2351,29 +3471,33 @@ impl Tournament {
         let mut writer = Writer::from_path(path)?;
         
         // Write header
-        writer.write_record(&[
-            "Opening", "Strategy", "Opponent", "Wins", "Win %"
+        writer.write_record([
+            "Rules", "Opening", "Strategy", "Opponent", "Wins", "Draws", "Win %"
         ])?;
         
         // Write data rows
         for result in &self.results {
             // Strategy1 vs Strategy2
             let win_percentage = (result.strategy1_wins as f64 / self.games_per_match as f64) * 100.0;
-            writer.write_record(&[
+            writer.write_record([
+                &result.rules.to_string(),
                 &result.opening,
                 &result.strategy1,
                 &result.strategy2,
                 &result.strategy1_wins.to_string(),
+                &result.draws.to_string(),
                 &format!("{:.2}", win_percentage),
             ])?;
             
             // Strategy2 vs Strategy1
             let win_percentage = (result.strategy2_wins as f64 / self.games_per_match as f64) * 100.0;
-            writer.write_record(&[
+            writer.write_record([
+                &result.rules.to_string(),
                 &result.opening,
                 &result.strategy2,
                 &result.strategy1,
                 &result.strategy2_wins.to_string(),
+                &result.draws.to_string(),
                 &format!("{:.2}", win_percentage),
             ])?;
         }
@@ -2386,14 +3510,15 @@ impl Tournament {
         let start_time = Instant::now();
         println!("Starting tournament with parallel execution...");
         
-        let strategy_names = vec![
-            'Random',
-            'ShortestPath',
-            'Defensive',
-            'Balanced',
-            'Adaptive',
-            'Minimax1',
-            'Minimax2',
+        let mut strategy_names = vec![
+            "Random",
+            "ShortestPath",
+            "Defensive",
+            "Balanced",
+            "Adaptive",
+            "Minimax1",
+            "Minimax2",
+            "IDMinimax1s",
             "Mirror",
             "SimulatedAnnealing0.5",
             "SimulatedAnnealing1.0",
@@ -2401,6 +3526,9 @@ impl Tournament {
             "MCTS1sec",
             "MCTS3sec",
         ];
+        if self.solution.is_some() {
+            strategy_names.push("Perfect");
+        }
         
         let opening_names = vec![
             "No Opening", 
@@ -2429,7 +3557,7 @@ impl Tournament {
         let num_threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
         println!("Using {} threads", num_threads);
         
-        let chunk_size = (match_configs.len() + num_threads - 1) / num_threads;
+        let chunk_size = match_configs.len().div_ceil(num_threads);
         
         // Use an Arc<Mutex<>> to collect results from threads
         let results = Arc::new(Mutex::new(Vec::new()));
@@ -2443,6 +3571,9 @@ impl Tournament {
             let board_size = self.board_size;
             let walls = self.walls;
             let games_per_match = self.games_per_match;
+            let rules = self.rules;
+            let weights = self.weights;
+            let solution = self.solution.clone();
             
             // Spawn a thread to process this chunk
             let handle = thread::spawn(move || {
@@ -2450,7 +3581,8 @@ impl Tournament {
                 let thread_start = Instant::now();
                 
                 // Create a tournament for this thread
-                let mut thread_tournament = Tournament::new(board_size, walls, games_per_match);
+                let mut thread_tournament = Tournament::new(board_size, walls, games_per_match, rules).with_weights(weights);
+                thread_tournament.solution = solution;
                 
                 // Process each match in this chunk
                 for (idx, (s1, s2, opening, disp)) in chunk_configs.iter().enumerate() {
@@ -2503,12 +3635,98 @@ pub fn main() {
         println!("Debug mode enabled");
     }
     
+    // Board size, walls and rules come from QUORIDOR_SIZE / QUORIDOR_WALLS /
+    // QUORIDOR_RULES (e.g. "long-walls+shared-walls"), defaulting to the standard game
+    let board_size = env::var("QUORIDOR_SIZE").ok().and_then(|v| v.parse().ok()).unwrap_or(9);
+    let walls = env::var("QUORIDOR_WALLS").ok().and_then(|v| v.parse().ok()).unwrap_or_else(|| default_walls(board_size));
+    let rules = match env::var("QUORIDOR_RULES").map_or(Ok(RuleSet::default()), |v| v.parse::<RuleSet>()) {
+        Ok(rules) if rules.player_count != 2 => Err(QuoridorError::UnsupportedPlayerCount(rules.player_count)),
+        parsed => parsed,
+    };
+    let rules = match rules.and_then(|rules| Quoridor::new(board_size, walls, rules, None).map(|_| rules)) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Cannot run a tournament: {}", e);
+            return;
+        }
+    };
+    println!("Board {}x{}, {} walls each, {} rules", board_size, board_size, walls, rules);
+    
+    // Minimax weights from the file named by QUORIDOR_WEIGHTS, if any
+    let weights = match env::var("QUORIDOR_WEIGHTS") {
+        Ok(path) => match EvalWeights::load(&path) {
+            Ok(weights) => weights,
+            Err(e) => {
+                eprintln!("Cannot load weights from '{}': {}", path, e);
+                return;
+            }
+        },
+        Err(_) => EvalWeights::PAPER,
+    };
+    
     // Create tournament 
     let mut tournament = Tournament::new(
-        9,   // board size
-        10,  // walls
+        board_size,
+        walls,
         30,   // games per match 
-    );
+        rules,
+    ).with_weights(weights);
+    
+    // QUORIDOR_TUNE names a file to write tuned weights to instead of
+    // running the tournament
+    if let Ok(path) = env::var("QUORIDOR_TUNE") {
+        let config = tuning::TuningConfig::default();
+        println!("Tuning weights over {} self-play games of {}...", config.games, config.strategy);
+        let tuned = match tuning::tune(&tournament, &config, &mut thread_rng()) {
+            Ok(tuned) => tuned,
+            Err(e) => {
+                eprintln!("Cannot tune with {}: {}", config.strategy, e);
+                return;
+            }
+        };
+        match tuned.save(&path) {
+            Ok(_) => println!("Tuned weights saved to '{}':\n{}", path, tuned),
+            Err(e) => eprintln!("Error writing weights: {}", e),
+        }
+        return;
+    }
+    
+    // QUORIDOR_SOLVE names a file to write the solved game on this board to
+    // instead of running the tournament; QUORIDOR_SOLUTION names one to load
+    // for the Perfect strategy
+    if let Ok(path) = env::var("QUORIDOR_SOLVE") {
+        println!("Solving {}x{} with {} walls each...", board_size, board_size, walls);
+        let start = Instant::now();
+        let solved = match SolutionTable::solve(board_size, walls) {
+            Ok(solved) => solved,
+            Err(e) => {
+                eprintln!("Cannot solve the board: {}", e);
+                return;
+            }
+        };
+        let board = bitboard::BitboardGame::new(board_size, walls).expect("the board was just solved");
+        println!("{} positions solved in {:.2?}; the start is a {:?}", solved.positions(), start.elapsed(), solved.value(&board));
+        match solved.save(&path) {
+            Ok(_) => println!("Solution saved to '{}'", path),
+            Err(e) => eprintln!("Error writing solution: {}", e),
+        }
+        return;
+    }
+    if let Ok(path) = env::var("QUORIDOR_SOLUTION") {
+        match SolutionTable::load(&path) {
+            Ok(solution) if (solution.size(), solution.walls()) == (board_size, walls) => {
+                tournament = tournament.with_solution(Arc::new(solution));
+            },
+            Ok(solution) => {
+                eprintln!("'{}' solves {}x{} with {} walls each, not this board", path, solution.size(), solution.size(), solution.walls());
+                return;
+            },
+            Err(e) => {
+                eprintln!("Cannot load solution from '{}': {}", path, e);
+                return;
+            }
+        }
+    }
     
     // Run th tournament using parallel execution
     tournament.run_tournament_parallel(debug_enabled);
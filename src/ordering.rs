@@ -0,0 +1,94 @@
+// Move ordering for the alpha-beta search.
+//
+// Alpha-beta prunes the most when the best move is searched first. Quoridor
+// has no captures to try first, so the likeliest good moves are ranked by
+// what the search has learned and by the pawns' routes: the move the
+// transposition table remembers, then the killer moves that caused a cutoff
+// at the same ply elsewhere in the tree, then walls across the opponent's
+// shortest route and pawn steps towards the goal, then other pawn steps and
+// other walls. Within each rank, moves that have caused many cutoffs
+// anywhere in the tree come first (the history heuristic).
+use std::collections::HashMap;
+
+use super::{Move, Quoridor};
+
+// Killer moves kept per ply
+const KILLERS: usize = 2;
+
+// Ranks, best first
+const TABLE_MOVE: u8 = 4;
+const KILLER: u8 = 3;
+const ON_ROUTE: u8 = 2; // A wall across the opponent's route or a step nearer the goal
+const PAWN: u8 = 1;
+const WALL: u8 = 0;
+
+#[derive(Default)]
+pub struct MoveOrdering {
+    killers: Vec<[Option<Move>; KILLERS]>, // By ply
+    history: HashMap<Move, u64>,
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Start a new search: killers belong to the old position's plies, but
+    // history is still a fair guide once it has faded a little
+    pub fn new_search(&mut self) {
+        self.killers.clear();
+        self.history.retain(|_, score| {
+            *score /= 2;
+            *score > 0
+        });
+    }
+
+    pub fn killers(&self, ply: usize) -> [Option<Move>; KILLERS] {
+        self.killers.get(ply).copied().unwrap_or_default()
+    }
+
+    pub fn history(&self, mv: Move) -> u64 {
+        self.history.get(&mv).copied().unwrap_or(0)
+    }
+
+    // Sort the side to move's moves best first. Moves of equal rank and
+    // history keep their order.
+    pub fn order(&self, game: &Quoridor, moves: &mut [Move], table_move: Option<Move>, ply: usize) {
+        let player = game.active_player;
+        let distance = game.distance_to_goal(player);
+        let opponent_route = game.shortest_route(game.leading_opponent(player));
+        let killers = self.killers(ply);
+
+        let rank = |mv: Move| {
+            if Some(mv) == table_move {
+                return TABLE_MOVE;
+            }
+            if killers.contains(&Some(mv)) {
+                return KILLER;
+            }
+            match mv {
+                Move::Pawn(to) if game.coord_of(to).is_some_and(|to| game.distance_from(player, to) < distance) => ON_ROUTE,
+                Move::Pawn(_) => PAWN,
+                Move::Wall(anchor, orientation) if game.coord_of(anchor)
+                    .is_some_and(|anchor| game.wall_crosses(anchor, orientation, &opponent_route)) => ON_ROUTE,
+                Move::Wall(..) => WALL,
+            }
+        };
+        moves.sort_by_cached_key(|&mv| std::cmp::Reverse((rank(mv), self.history(mv))));
+    }
+
+    // The move refuted the position `depth` plies from the horizon at `ply`:
+    // try it early at this ply elsewhere, and everywhere in proportion to
+    // how deep a tree it cut off
+    pub fn record_cutoff(&mut self, mv: Move, ply: usize, depth: usize) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; KILLERS]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers.rotate_right(1);
+            killers[0] = Some(mv);
+        }
+        *self.history.entry(mv).or_insert(0) += (depth * depth) as u64;
+    }
+}
@@ -0,0 +1,502 @@
+// Exact solutions of whole games on small boards.
+//
+// Walls never come off the board, so a game passes through a chain of
+// layers, each one a set of walls on the board and a count of walls in each
+// hand. Within a layer only the pawns and the side to move change, and
+// pawns can go back and forth; a wall leads out to a layer with one more
+// wall. Layers are solved from the fullest down, so by the time a layer is
+// solved every wall placed in it leads somewhere already solved, and inside
+// the layer the pawn moves are settled by retrograde analysis as in
+// `endgame`: positions are taken in order of how many moves remain, so each
+// one settles at its quickest win or its slowest loss, and whatever never
+// settles is a draw. As there, the move limit and the repetition rule are
+// left out, and a side with no move at all loses.
+//
+// The table keeps one byte per position, the value for the side to move and
+// the moves left, which is enough to pick a best move by looking one move
+// ahead. Only positions a game can reach are kept: layers whose walls leave
+// both pawns somewhere to stand with a way to their goals, and in each, the
+// pawns on two different squares with a way to their goals. A finished game
+// needs no byte, as the side to move has lost it. A 5x5 board with 3 walls
+// each has about 450,000 layers and 300 million positions, a minute and a
+// half's work and a 320MB file; a 7x7 board with a wall each takes seconds.
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use super::bitboard::BitboardGame;
+use super::{Move, Orientation, Player, QuoridorError, Square};
+
+const MAGIC: &[u8; 4] = b"QSOL";
+
+// Raised whenever the file layout changes, so old files are refused
+const VERSION: u8 = 2;
+
+// Longest win or loss a table byte can hold
+const MAX_PLIES: usize = 127;
+
+// What a position is worth to the side to move with best play on both
+// sides, and how many moves (of both sides) that takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Win(usize),
+    Loss(usize),
+    Draw,
+}
+
+impl Value {
+    fn encode(self) -> u8 {
+        match self {
+            Value::Draw => 0,
+            Value::Win(plies) => plies as u8,
+            Value::Loss(plies) => 128 + plies as u8,
+        }
+    }
+
+    fn decode(byte: u8) -> Self {
+        match byte {
+            0 => Value::Draw,
+            1..=127 => Value::Win(byte as usize),
+            _ => Value::Loss(byte as usize - 128),
+        }
+    }
+}
+
+// Everything about a position but where the pawns are and whose turn it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Layer {
+    hwalls: u128,
+    vwalls: u128,
+    walls_available: [usize; 2],
+}
+
+impl Layer {
+    fn of(game: &BitboardGame) -> Self {
+        Layer { hwalls: game.hwalls, vwalls: game.vwalls, walls_available: game.walls_available }
+    }
+
+    fn board(&self, size: usize) -> BitboardGame {
+        let mut board = BitboardGame::new(size, 0).expect("solved boards fit a bitboard");
+        for (walls, orientation) in [(self.hwalls, Orientation::Horizontal), (self.vwalls, Orientation::Vertical)] {
+            for square in (0..size * size).filter(|&square| walls >> square & 1 != 0) {
+                board.place_wall(board.coord(square), orientation);
+            }
+        }
+        board.walls_available = self.walls_available;
+        board
+    }
+}
+
+// Where a layer's positions are kept: the squares off their goals each
+// pawn can reach its goal from, and the first position's place in the table
+#[derive(Debug, Clone, Copy)]
+struct Shape {
+    reach: [u128; 2],
+    offset: usize,
+}
+
+impl Shape {
+    fn of(board: &BitboardGame, offset: usize) -> Self {
+        let reach = [Player::Player1, Player::Player2].map(|player| {
+            let goal = board.goal_mask(player);
+            board.reachable(goal) & !goal
+        });
+        Shape { reach, offset }
+    }
+
+    // Positions: both pawns' squares, different and each in reach, and the
+    // side to move
+    fn len(&self) -> usize {
+        let [first, second] = self.reach.map(|reach| reach.count_ones() as usize);
+        (first * second - (self.reach[0] & self.reach[1]).count_ones() as usize) * 2
+    }
+
+    // Pairs go by the first pawn's square, then the second's among the
+    // squares left to it, counting only squares in reach
+    fn position(&self, pawns: [usize; 2], side: usize) -> Option<usize> {
+        let [first, second] = pawns;
+        if first == second || self.reach[0] >> first & 1 == 0 || self.reach[1] >> second & 1 == 0 {
+            return None;
+        }
+        let below = |square: usize| (1u128 << square) - 1;
+        let count = |mask: u128| mask.count_ones() as usize;
+        let earlier_pairs = count(self.reach[0] & below(first)) * count(self.reach[1])
+            - count(self.reach[0] & self.reach[1] & below(first));
+        let skipped = usize::from(self.reach[1] >> first & 1 != 0 && first < second);
+        Some((earlier_pairs + count(self.reach[1] & below(second)) - skipped) * 2 + side)
+    }
+}
+
+// A wall that fits in a layer: the squares from which each pawn still
+// reaches its goal with it down, and where each side ends up by placing it
+struct Exit {
+    reach: [u128; 2],
+    layers: [Option<usize>; 2],
+}
+
+pub struct SolutionTable {
+    size: usize,
+    walls: usize,
+    layers: Vec<Layer>,
+    shapes: Vec<Shape>,
+    index: HashMap<Layer, usize>,
+    values: Vec<u8>,
+}
+
+impl SolutionTable {
+    // Solve every position of the standard game on a `size` board with
+    // `walls` walls each
+    pub fn solve(size: usize, walls: usize) -> Result<Self, QuoridorError> {
+        let root = BitboardGame::new(size, walls)?;
+
+        // Every combination of walls on the board and in hand, fewest walls
+        // placed first. As in `get_legal_walls`, a wall must leave both pawns
+        // a way to their goals, from some pair of squares at least.
+        let mut layers = vec![Layer::of(&root)];
+        let mut index = HashMap::from([(layers[0], 0)]);
+        let mut next = 0;
+        while next < layers.len() {
+            let board = layers[next].board(size);
+            for (coord, orientation) in Self::slots(size).filter(|&(coord, orientation)| board.wall_fits(coord, orientation)) {
+                let mut after = board;
+                after.place_wall(coord, orientation);
+                if Shape::of(&after, 0).len() == 0 {
+                    continue;
+                }
+                for side in (0..2).filter(|&side| board.walls_available[side] > 0) {
+                    let mut placed = after;
+                    placed.walls_available[side] -= 1;
+                    let layer = Layer::of(&placed);
+                    index.entry(layer).or_insert_with(|| {
+                        layers.push(layer);
+                        layers.len() - 1
+                    });
+                }
+            }
+            next += 1;
+        }
+
+        let shapes = Self::shapes(size, &layers);
+        let mut table = SolutionTable {
+            size,
+            walls,
+            values: vec![0; shapes.last().map_or(0, |shape| shape.offset + shape.len())],
+            layers,
+            shapes,
+            index,
+        };
+        let mut scratch = Scratch::default();
+        for layer in (0..table.layers.len()).rev() {
+            table.solve_layer(layer, &mut scratch)?;
+        }
+        Ok(table)
+    }
+
+    // Each layer's positions, laid end to end
+    fn shapes(size: usize, layers: &[Layer]) -> Vec<Shape> {
+        let mut offset = 0;
+        layers.iter().map(|layer| {
+            let shape = Shape::of(&layer.board(size), offset);
+            offset += shape.len();
+            shape
+        }).collect()
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn walls(&self) -> usize {
+        self.walls
+    }
+
+    // Positions in the table
+    pub fn positions(&self) -> usize {
+        self.values.len()
+    }
+
+    // None for a position no game reaches, or one the table does not cover
+    pub fn value(&self, game: &BitboardGame) -> Option<Value> {
+        if game.size != self.size {
+            return None;
+        }
+        let layer = *self.index.get(&Layer::of(game))?;
+        let arrived = [Player::Player1, Player::Player2].iter().zip(game.pawns)
+            .any(|(&player, square)| game.goal_mask(player) >> square & 1 != 0);
+        if arrived {
+            return Some(Value::Loss(0));
+        }
+        let shape = &self.shapes[layer];
+        let position = shape.position(game.pawns, game.active_player.index())?;
+        Some(Value::decode(self.values[shape.offset + position]))
+    }
+
+    // The quickest win, the slowest loss or a move that keeps the draw; none
+    // once the game is over or for a position the table does not cover
+    pub fn best_move(&self, game: &BitboardGame) -> Option<Move> {
+        let value = self.value(game)?;
+        game.legal_moves().into_iter().find(|&mv| {
+            let mut after = *game;
+            after.make_move(mv);
+            match (value, self.value(&after)) {
+                (Value::Win(plies), Some(Value::Loss(left))) => left + 1 == plies,
+                (Value::Loss(plies), Some(Value::Win(left))) => left + 1 == plies,
+                (Value::Draw, Some(Value::Draw)) => true,
+                _ => false,
+            }
+        })
+    }
+
+    // The format version, board and wall count, then each layer's walls,
+    // then a byte per position, layer by layer
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, self.size as u8, self.walls as u8]);
+        bytes.extend((self.layers.len() as u32).to_le_bytes());
+        for layer in &self.layers {
+            bytes.extend(layer.hwalls.to_le_bytes());
+            bytes.extend(layer.vwalls.to_le_bytes());
+            bytes.extend(layer.walls_available.map(|left| left as u8));
+        }
+        bytes.extend(&self.values);
+        std::fs::write(path, bytes)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let malformed = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("not a solution table: {}", what));
+        let bytes = std::fs::read(path)?;
+        let header = MAGIC.len() + 7;
+        if bytes.len() < header || !bytes.starts_with(MAGIC) {
+            return Err(malformed("bad header"));
+        }
+        let [version, size, walls] = [0, 1, 2].map(|field| bytes[MAGIC.len() + field]);
+        if version != VERSION {
+            return Err(malformed(&format!("format version {}, not {}", version, VERSION)));
+        }
+        let (size, walls) = (size as usize, walls as usize);
+        let count = u32::from_le_bytes(bytes[MAGIC.len() + 3..header].try_into().expect("four bytes")) as usize;
+        let board = BitboardGame::new(size, walls).map_err(|e| malformed(&e.to_string()))?;
+
+        const LAYER_BYTES: usize = 34;
+        let values_start = count.checked_mul(LAYER_BYTES).and_then(|len| len.checked_add(header))
+            .filter(|&start| start <= bytes.len())
+            .ok_or_else(|| malformed("wrong length"))?;
+        let layers: Vec<Layer> = bytes[header..values_start].chunks(LAYER_BYTES)
+            .map(|chunk| Layer {
+                hwalls: u128::from_le_bytes(chunk[..16].try_into().expect("sixteen bytes")),
+                vwalls: u128::from_le_bytes(chunk[16..32].try_into().expect("sixteen bytes")),
+                walls_available: [chunk[32] as usize, chunk[33] as usize],
+            })
+            .collect();
+
+        // Walls only on their slots, and as many in all as the players started with
+        let slots = Self::slots(size).fold(0u128, |slots, (coord, _)| slots | 1 << board.index_of(Square::at(size, coord)));
+        let sound = |layer: &Layer| {
+            let placed = (layer.hwalls.count_ones() + layer.vwalls.count_ones()) as usize;
+            (layer.hwalls | layer.vwalls) & !slots == 0
+                && layer.walls_available.iter().all(|&left| left <= walls)
+                && placed + layer.walls_available.iter().sum::<usize>() == 2 * walls
+        };
+        if !layers.iter().all(sound) {
+            return Err(malformed("impossible walls"));
+        }
+        let index: HashMap<Layer, usize> = layers.iter().enumerate().map(|(number, &layer)| (layer, number)).collect();
+        if index.len() != count {
+            return Err(malformed("repeated layers"));
+        }
+
+        let shapes = Self::shapes(size, &layers);
+        if bytes.len() - values_start != shapes.last().map_or(0, |shape| shape.offset + shape.len()) {
+            return Err(malformed("wrong length"));
+        }
+        Ok(SolutionTable {
+            size,
+            walls,
+            values: bytes[values_start..].to_vec(),
+            layers,
+            shapes,
+            index,
+        })
+    }
+
+    fn slots(size: usize) -> impl Iterator<Item = ((usize, usize), Orientation)> {
+        (1..size).flat_map(move |row| (0..size - 1).flat_map(move |col| {
+            [Orientation::Horizontal, Orientation::Vertical].map(|orientation| ((row, col), orientation))
+        }))
+    }
+
+    // Settle every position of a layer, given the layers its walls lead to
+    fn solve_layer(&mut self, layer: usize, scratch: &mut Scratch) -> Result<(), QuoridorError> {
+        let size = self.size;
+        let board = self.layers[layer].board(size);
+        let shape = self.shapes[layer];
+        let layer_len = shape.len();
+        let goals = [board.goal_mask(Player::Player1), board.goal_mask(Player::Player2)];
+        let exits: Vec<Exit> = Self::slots(size)
+            .filter(|&(coord, orientation)| board.wall_fits(coord, orientation))
+            .map(|(coord, orientation)| {
+                let mut after = board;
+                after.place_wall(coord, orientation);
+                let reach = goals.map(|goal| after.reachable(goal));
+                let layers = [0, 1].map(|side| (board.walls_available[side] > 0).then(|| {
+                    let mut placed = Layer::of(&after);
+                    placed.walls_available[side] -= 1;
+                    self.index.get(&placed).copied()
+                }).flatten());
+                Exit { reach, layers }
+            })
+            .collect();
+
+        scratch.reset(layer_len);
+        let squares = |mask: u128| (0..size * size).filter(move |&square| mask >> square & 1 != 0);
+        for first in squares(shape.reach[0]) {
+            for second in squares(shape.reach[1]).filter(|&second| second != first) {
+                let pawns = [first, second];
+                for side in 0..2 {
+                    let position = shape.position(pawns, side).expect("both pawns are in reach");
+
+                    // A step onto the goal wins at once; any other stays in
+                    // the layer, where the pawn could still reach its goal
+                    let mut moving = board;
+                    moving.pawns = pawns;
+                    let player = if side == 0 { Player::Player1 } else { Player::Player2 };
+                    let mut moves = 0;
+                    for mv in moving.get_legal_moves(player) {
+                        let mut after = pawns;
+                        after[side] = moving.index_of(mv.square());
+                        moves += 1;
+                        if goals[side] >> after[side] & 1 != 0 {
+                            scratch.settle_later(position, Value::Win(1))?;
+                            continue;
+                        }
+                        let next = shape.position(after, 1 - side).expect("a pawn move keeps to where the goal is in reach");
+                        scratch.successors.push((next, position));
+                    }
+
+                    let mut wins_for_opponent = 0;
+                    for exit in &exits {
+                        let Some(next) = exit.layers[side] else {
+                            continue;
+                        };
+                        if exit.reach[0] >> first & 1 == 0 || exit.reach[1] >> second & 1 == 0 {
+                            continue;
+                        }
+                        moves += 1;
+                        let next = &self.shapes[next];
+                        let after = next.position(pawns, 1 - side).expect("both pawns are still in reach");
+                        match Value::decode(self.values[next.offset + after]) {
+                            Value::Loss(plies) => scratch.settle_later(position, Value::Win(plies + 1))?,
+                            Value::Win(plies) => {
+                                wins_for_opponent += 1;
+                                scratch.longest[position] = scratch.longest[position].max(plies);
+                            },
+                            Value::Draw => {},
+                        }
+                    }
+
+                    scratch.unsettled[position] = moves - wins_for_opponent;
+                    if moves == 0 {
+                        scratch.settle_later(position, Value::Loss(0))?;
+                    } else if moves == wins_for_opponent {
+                        scratch.settle_later(position, Value::Loss(scratch.longest[position] + 1))?;
+                    }
+                }
+            }
+        }
+        scratch.link_predecessors(layer_len);
+
+        // Take positions in order of the moves left, so the first result a
+        // position gets is its best
+        let mut plies = 0;
+        while plies < scratch.pending.len() {
+            let pending = std::mem::take(&mut scratch.pending[plies]);
+            for (position, value) in pending {
+                if scratch.values[position].is_some() {
+                    continue;
+                }
+                scratch.values[position] = Some(value);
+                for index in scratch.first_predecessor[position]..scratch.first_predecessor[position + 1] {
+                    let before = scratch.predecessors[index];
+                    if scratch.values[before].is_some() {
+                        continue;
+                    }
+                    match value {
+                        Value::Loss(plies) => scratch.settle_later(before, Value::Win(plies + 1))?,
+                        Value::Win(plies) => {
+                            scratch.unsettled[before] -= 1;
+                            scratch.longest[before] = scratch.longest[before].max(plies);
+                            if scratch.unsettled[before] == 0 {
+                                scratch.settle_later(before, Value::Loss(scratch.longest[before] + 1))?;
+                            }
+                        },
+                        Value::Draw => unreachable!("draws are never settled"),
+                    }
+                }
+            }
+            plies += 1;
+        }
+
+        let values = &mut self.values[shape.offset..shape.offset + layer_len];
+        for (byte, value) in values.iter_mut().zip(&scratch.values) {
+            *byte = value.unwrap_or(Value::Draw).encode();
+        }
+        Ok(())
+    }
+}
+
+// Working space for solving a layer, kept from one layer to the next
+#[derive(Default)]
+struct Scratch {
+    values: Vec<Option<Value>>,
+    // Moves not yet known to lose, and the longest win among those that do
+    unsettled: Vec<usize>,
+    longest: Vec<usize>,
+    // Pawn moves as (after, before), then grouped by the position after
+    successors: Vec<(usize, usize)>,
+    first_predecessor: Vec<usize>,
+    predecessors: Vec<usize>,
+    // Results waiting to be settled, by the moves left
+    pending: Vec<Vec<(usize, Value)>>,
+}
+
+impl Scratch {
+    fn reset(&mut self, len: usize) {
+        self.values.clear();
+        self.values.resize(len, None);
+        self.unsettled.clear();
+        self.unsettled.resize(len, 0);
+        self.longest.clear();
+        self.longest.resize(len, 0);
+        self.successors.clear();
+        self.pending.iter_mut().for_each(Vec::clear);
+    }
+
+    fn settle_later(&mut self, position: usize, value: Value) -> Result<(), QuoridorError> {
+        let plies = match value {
+            Value::Win(plies) | Value::Loss(plies) => plies,
+            Value::Draw => unreachable!("draws are never settled"),
+        };
+        if plies > MAX_PLIES {
+            return Err(QuoridorError::SolutionTooLong(MAX_PLIES));
+        }
+        if self.pending.len() <= plies {
+            self.pending.resize_with(plies + 1, Vec::new);
+        }
+        self.pending[plies].push((position, value));
+        Ok(())
+    }
+
+    fn link_predecessors(&mut self, len: usize) {
+        self.successors.sort_unstable();
+        self.first_predecessor.clear();
+        self.first_predecessor.resize(len + 1, 0);
+        for &(after, _) in &self.successors {
+            self.first_predecessor[after + 1] += 1;
+        }
+        for position in 0..len {
+            self.first_predecessor[position + 1] += self.first_predecessor[position];
+        }
+        self.predecessors.clear();
+        self.predecessors.extend(self.successors.iter().map(|&(_, before)| before));
+    }
+}
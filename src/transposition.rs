@@ -0,0 +1,80 @@
+// Transposition table for the search strategies.
+//
+// Quoridor reaches the same position by many move orders (a pawn step and a
+// wall far away commute), so a search keeps what it learned about each
+// position under its Zobrist key: the depth searched, the score (for the
+// side to move, which the key includes) and whether that score is exact or
+// only a bound, and the best move found. The table is a fixed number of
+// slots chosen from a memory budget; a key picks its slot by its low bits
+// and a newer or deeper result replaces an older one.
+use super::Move;
+
+// What a stored score says about the position's true value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower, // The search failed high: the value is at least the score
+    Upper, // The search failed low: the value is at most the score
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableEntry {
+    pub key: u64,
+    pub depth: usize,
+    pub score: f64,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+    generation: u32,
+}
+
+pub struct TranspositionTable {
+    slots: Vec<Option<TableEntry>>,
+    generation: u32,
+}
+
+impl TranspositionTable {
+    // As many slots as fit in `megabytes`, rounded down to a power of two.
+    // Zero gives a table that stores nothing.
+    pub fn new(megabytes: usize) -> Self {
+        let fit = megabytes * 1024 * 1024 / std::mem::size_of::<Option<TableEntry>>();
+        let slots = if fit == 0 { 0 } else { 1 << fit.ilog2() };
+        TranspositionTable {
+            slots: vec![None; slots],
+            generation: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    // Start a new search: entries from earlier ones are still read but give
+    // way to anything stored from now on
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+    }
+
+    fn slot(&self, key: u64) -> Option<usize> {
+        (!self.slots.is_empty()).then(|| key as usize & (self.slots.len() - 1))
+    }
+
+    pub fn probe(&self, key: u64) -> Option<&TableEntry> {
+        let slot = self.slot(key)?;
+        self.slots[slot].as_ref().filter(|entry| entry.key == key)
+    }
+
+    pub fn store(&mut self, key: u64, depth: usize, score: f64, bound: Bound, best_move: Option<Move>) {
+        let Some(slot) = self.slot(key) else {
+            return;
+        };
+        let generation = self.generation;
+        let keep = self.slots[slot].is_some_and(|entry| entry.generation == generation && entry.depth > depth);
+        if !keep {
+            self.slots[slot] = Some(TableEntry { key, depth, score, bound, best_move, generation });
+        }
+    }
+}
@@ -0,0 +1,170 @@
+// Tuning the evaluation weights by self-play.
+//
+// Texel's method: play games, keep every position with how the game went for
+// the side to move, and look for the weights whose evaluation, squashed into
+// a win probability by a logistic curve, best predicts those results (least
+// mean squared error). The curve's scale is fitted to the starting weights
+// first and then held, which pins down how large the weights are.
+//
+// The error is minimised by SPSA (simultaneous perturbation stochastic
+// approximation): each step nudges every weight at once by a random plus or
+// minus, and the two errors either side estimate the slope in all
+// directions, so a step costs two evaluations however many weights there
+// are. Weights are stepped in units of their starting size, as they range
+// from below one to over ten, and the slope is calibrated on a few trial
+// estimates so that the first step moves them by about `step` units.
+use rand::prelude::*;
+
+use super::evaluation::C3Evaluator;
+use super::{EvalWeights, Quoridor, QuoridorError, Tournament};
+
+// A position from self-play: its features for the side to move (see
+// `C3Evaluator::features`), and the game's result for that side: 1 won,
+// 0.5 drawn, 0 lost
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub features: [f64; 3],
+    pub result: f64,
+}
+
+pub struct TuningConfig {
+    pub strategy: String,    // Plays both sides of every game, e.g. "Minimax1"
+    pub games: usize,
+    pub random_plies: usize, // Random moves opening each game, so that games differ
+    pub iterations: usize,   // SPSA steps
+    pub step: f64,           // How far the first step moves the weights, in their starting sizes
+    pub perturbation: f64,   // How far the weights are nudged to measure the slope, likewise
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        TuningConfig {
+            strategy: "Minimax1".to_string(),
+            games: 200,
+            random_plies: 4,
+            iterations: 2000,
+            step: 0.1,
+            perturbation: 0.05,
+        }
+    }
+}
+
+// Self-play, then SPSA from the tournament's weights
+pub fn tune<R: Rng>(tournament: &Tournament, config: &TuningConfig, rng: &mut R) -> Result<EvalWeights, QuoridorError> {
+    let samples = self_play(tournament, config, rng)?;
+    Ok(spsa(&samples, tournament.weights, config, rng))
+}
+
+// Play `config.games` games on the tournament's board, under its rules and
+// weights, and sample every position. Games a strategy could not finish
+// (no move, or an illegal one) are left out. An error if the strategy
+// cannot be created.
+pub fn self_play<R: Rng>(tournament: &Tournament, config: &TuningConfig, rng: &mut R) -> Result<Vec<Sample>, QuoridorError> {
+    let mut samples = Vec::new();
+    for _ in 0..config.games {
+        let mut game = Quoridor::new(tournament.board_size, tournament.walls, tournament.rules, None)
+            .expect("tournament board configuration is valid");
+        let mut strategies = game.pawn_positions.keys()
+            .map(|&player| Ok((player, tournament.create_strategy(&config.strategy, "No Opening", player)?)))
+            .collect::<Result<Vec<_>, QuoridorError>>()?;
+        
+        let mut positions = Vec::new();
+        let result = loop {
+            if let Some(result) = game.outcome() {
+                break Some(result);
+            }
+            let player = game.active_player;
+            positions.push((C3Evaluator::features(&game, player), player));
+            
+            let mv = if game.history.len() < config.random_plies {
+                game.legal_moves().choose(rng).copied()
+            } else {
+                let (_, strategy) = strategies.iter_mut().find(|(owner, _)| *owner == player).expect("every player has a strategy");
+                strategy.choose_move(&game)
+            };
+            if mv.is_none_or(|mv| game.apply(mv).is_err()) {
+                break None;
+            }
+        };
+        
+        let Some(result) = result else {
+            continue;
+        };
+        samples.extend(positions.into_iter().map(|(features, player)| Sample {
+            features,
+            result: match result.winner() {
+                Some(winner) if winner == player => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            },
+        }));
+    }
+    Ok(samples)
+}
+
+fn predict(sample: &Sample, weights: &[f64; 3], scale: f64) -> f64 {
+    let score: f64 = sample.features.iter().zip(weights).map(|(feature, weight)| feature * weight).sum();
+    1.0 / (1.0 + (-scale * score).exp())
+}
+
+// Mean squared error of the weights' predictions, with the logistic curve
+// stretched by `scale`
+pub fn loss(samples: &[Sample], weights: EvalWeights, scale: f64) -> f64 {
+    let weights = weights.to_array();
+    let total: f64 = samples.iter().map(|sample| (sample.result - predict(sample, &weights, scale)).powi(2)).sum();
+    total / samples.len().max(1) as f64
+}
+
+// The logistic scale that best fits the weights, from 0.001 to 1
+pub fn fit_scale(samples: &[Sample], weights: EvalWeights) -> f64 {
+    (0..=60)
+        .map(|i| 10f64.powf(-3.0 + i as f64 / 20.0))
+        .min_by(|&a, &b| loss(samples, weights, a).total_cmp(&loss(samples, weights, b)))
+        .expect("the range is not empty")
+}
+
+// Trial slope estimates averaged to calibrate the step size
+const CALIBRATION_ESTIMATES: usize = 10;
+
+// Minimise `loss` from `start` by SPSA, with the usual gains: step k moves
+// by step / (k + 1)^0.602 and measures with perturbation / (k + 1)^0.101
+pub fn spsa<R: Rng>(samples: &[Sample], start: EvalWeights, config: &TuningConfig, rng: &mut R) -> EvalWeights {
+    let scale = fit_scale(samples, start);
+    let units = start.to_array().map(|weight| weight.abs().max(0.1));
+    let to_weights = |theta: [f64; 3]| {
+        let mut weights = theta;
+        for (weight, unit) in weights.iter_mut().zip(units) {
+            *weight *= unit;
+        }
+        EvalWeights::from_array(weights)
+    };
+    
+    // The slope along a random direction, as its size in each coordinate
+    // (the same for all) and the direction's signs
+    let mut estimate = |theta: [f64; 3], perturbation: f64| {
+        let delta: [f64; 3] = std::array::from_fn(|_| if rng.gen::<bool>() { 1.0 } else { -1.0 });
+        let nudged = |sign: f64| to_weights(std::array::from_fn(|i| theta[i] + sign * perturbation * delta[i]));
+        let rise = loss(samples, nudged(1.0), scale) - loss(samples, nudged(-1.0), scale);
+        (rise / (2.0 * perturbation), delta)
+    };
+    
+    let mut theta = start.to_array();
+    for (value, unit) in theta.iter_mut().zip(units) {
+        *value /= unit;
+    }
+    let slope = (0..CALIBRATION_ESTIMATES)
+        .map(|_| estimate(theta, config.perturbation).0.abs())
+        .sum::<f64>() / CALIBRATION_ESTIMATES as f64;
+    if slope == 0.0 {
+        return start;
+    }
+    
+    for k in 0..config.iterations {
+        let step = config.step / slope / (k as f64 + 1.0).powf(0.602);
+        let (rise, delta) = estimate(theta, config.perturbation / (k as f64 + 1.0).powf(0.101));
+        for (value, direction) in theta.iter_mut().zip(delta) {
+            *value -= step * rise / direction;
+        }
+    }
+    to_weights(theta)
+}
@@ -0,0 +1,48 @@
+// Zobrist keys for Quoridor positions.
+//
+// A position's key is the XOR of one random 64-bit number per fact about
+// it: each pawn on its square, each wall in its slot, each player's count of
+// walls left and the side to move. Making a move only changes a few facts,
+// so the key is updated by XORing out the old numbers and XORing in the new.
+//
+// Rather than a table per board size, a fact's number is a SplitMix64 mix of
+// an index that names it, which makes the keys the same on every run and for
+// squares of any board up to MAX_BOARD_SIZE.
+use super::{Coord, Orientation, Player, MAX_BOARD_SIZE};
+
+// Index ranges of the kinds of fact, kept apart by the top bits
+const PAWN: u64 = 1 << 60;
+const WALL: u64 = 2 << 60;
+const WALLS_LEFT: u64 = 3 << 60;
+const SIDE_TO_MOVE: u64 = 4 << 60;
+
+fn mix(index: u64) -> u64 {
+    let mut z = index.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn square((row, col): Coord) -> u64 {
+    (row * MAX_BOARD_SIZE + col) as u64
+}
+
+pub fn pawn(player: Player, coord: Coord) -> u64 {
+    mix(PAWN | (player.index() as u64) << 16 | square(coord))
+}
+
+pub fn wall(coord: Coord, orientation: Orientation) -> u64 {
+    let orientation = match orientation {
+        Orientation::Horizontal => 0,
+        Orientation::Vertical => 1,
+    };
+    mix(WALL | orientation << 16 | square(coord))
+}
+
+pub fn walls_left(player: Player, count: usize) -> u64 {
+    mix(WALLS_LEFT | (player.index() as u64) << 16 | count as u64)
+}
+
+pub fn side_to_move(player: Player) -> u64 {
+    mix(SIDE_TO_MOVE | player.index() as u64)
+}
@@ -0,0 +1,74 @@
+// The bitboard against the graph-backed game over random playouts: at every
+// position both must offer the same pawn moves and walls, agree on how far
+// each pawn is from its goal, and stay in step as the same moves are played.
+use quoridor::main::bitboard::BitboardGame;
+use quoridor::main::*;
+use rand::prelude::*;
+
+fn sorted(moves: Vec<Move>) -> Vec<String> {
+    let mut names: Vec<String> = moves.into_iter().map(|mv| mv.to_string()).collect();
+    names.sort();
+    names
+}
+
+fn assert_agree(game: &Quoridor, board: &BitboardGame) {
+    let state = &game.state_string;
+    assert_eq!(*board, BitboardGame::from_quoridor(game).unwrap(), "{}", state);
+    assert_eq!(board.active_player, game.active_player, "{}", state);
+    for player in [Player::Player1, Player::Player2] {
+        assert_eq!(sorted(board.get_legal_moves(player)), sorted(game.get_legal_moves(player)), "{}", state);
+        assert_eq!(board.get_legal_walls(player), game.get_legal_walls(player), "{}", state);
+        assert_eq!(board.distance_to_goal(player), game.distance_to_goal(player), "{}", state);
+        assert_eq!(board.moves_to_next_row(player), game.moves_to_next_row(player), "{}", state);
+    }
+}
+
+#[test]
+fn random_playouts_agree_move_by_move() {
+    let mut rng = StdRng::seed_from_u64(5);
+    for (size, walls) in [(5, 3), (7, 6), (9, 10), (11, 16)] {
+        for _ in 0..4 {
+            let mut game = Quoridor::new(size, walls, RuleSet::default(), None).unwrap();
+            let mut board = BitboardGame::new(size, walls).unwrap();
+            assert_agree(&game, &board);
+
+            while game.outcome().is_none() {
+                // Walls are most of the moves; lean on pawn moves so games
+                // get somewhere before the walls run out
+                let moves = if rng.gen_bool(0.5) { game.get_legal_moves(game.active_player) } else { game.legal_moves() };
+                let mv = *moves.choose(&mut rng).unwrap();
+                assert_eq!(board.win_check(mv), game.win_check(mv), "{} in {}", mv, game.state_string);
+                board.apply(mv).unwrap_or_else(|e| panic!("{} in {}: {}", mv, game.state_string, e));
+                game.apply(mv).unwrap();
+                if game.outcome().is_none() {
+                    assert_agree(&game, &board);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn both_boards_refuse_the_same_walls() {
+    let mut rng = StdRng::seed_from_u64(55);
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    let mut board = BitboardGame::new(9, 10).unwrap();
+    for _ in 0..30 {
+        let player = game.active_player;
+        for row in 0..9 {
+            for col in 0..9 {
+                for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+                    assert_eq!(
+                        board.wall_check(player, (row, col), orientation),
+                        game.wall_check(player, (row, col), orientation),
+                        "{:?} {:?} in {}", (row, col), orientation, game.state_string
+                    );
+                }
+            }
+        }
+        let moves = game.legal_moves();
+        let mv = *moves.iter().filter(|mv| !game.win_check(**mv)).choose(&mut rng).unwrap();
+        board.apply(mv).unwrap();
+        game.apply(mv).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+// Boards from 5x5 to 19x19: notation with double-digit ranks, state strings,
+// size and wall-count validation, openings, and whole games between strategies.
+use quoridor::main::*;
+
+#[test]
+fn double_digit_ranks_round_trip() {
+    for notation in ["a10", "s19", "j10h", "k12v"] {
+        let mv: Move = notation.parse().unwrap();
+        assert_eq!(mv.to_string(), notation);
+    }
+    assert_eq!("a10".parse::<Move>(), Ok(Move::Pawn(Square::new(0, 10))));
+    assert_eq!("E2".parse::<Move>(), "e2".parse::<Move>());
+    assert_eq!("J10h".parse::<Move>(), "j10h".parse::<Move>());
+}
+
+#[test]
+fn rows_count_down_from_the_top_rank() {
+    for size in [5, 9, 19] {
+        let game = Quoridor::new(size, 0, RuleSet::default(), None).unwrap();
+        assert_eq!(game.algebraic_to_coord("a1"), Ok((size - 1, 0)));
+        assert_eq!(game.coord_to_algebraic((0, 0)), format!("a{}", size));
+        assert_eq!(game.pawn_positions[&Player::Player1], (size - 1, (size - 1) / 2));
+        assert!(game.goal_positions[&Player::Player1].iter().all(|&(row, _)| row == 0));
+    }
+}
+
+#[test]
+fn state_string_round_trips_on_large_boards() {
+    let state = "a10j12 / r3c18 / j1 j19 / 40 41 / 2";
+    let game = Quoridor::new(19, 45, RuleSet::default(), Some(state)).unwrap();
+    
+    assert_eq!(game.hwall_positions, vec![(9, 0), (7, 9)]);
+    assert_eq!(game.vwall_positions, vec![(16, 17), (1, 2)]);
+    assert_eq!(game.state_string, state);
+    assert_eq!(Quoridor::new(19, 45, RuleSet::default(), Some(&game.state_string)).unwrap().state_string, state);
+}
+
+#[test]
+fn board_size_and_walls_are_validated() {
+    assert_eq!(Quoridor::new(4, 2, RuleSet::default(), None).err(), Some(QuoridorError::UnsupportedBoardSize(4)));
+    assert_eq!(Quoridor::new(20, 10, RuleSet::default(), None).err(), Some(QuoridorError::UnsupportedBoardSize(20)));
+    assert_eq!(Quoridor::new(5, 11, RuleSet::default(), None).err(), Some(QuoridorError::TooManyWalls(11, 5)));
+    
+    for size in MIN_BOARD_SIZE..=MAX_BOARD_SIZE {
+        assert!(Quoridor::new(size, default_walls(size), RuleSet::default(), None).is_ok(), "{}x{}", size, size);
+        assert!(Quoridor::new(size, max_walls(size, RuleSet::default()), RuleSet::default(), None).is_ok(), "{}x{}", size, size);
+    }
+    assert_eq!(default_walls(9), 10);
+    assert_eq!(default_walls(5), 3);
+}
+
+#[test]
+fn openings_keep_their_shape_on_other_boards() {
+    let names = |moves: Vec<Move>| moves.iter().map(|mv| mv.to_string()).collect::<Vec<_>>();
+    
+    assert_eq!(names(get_opening_moves("Standard Opening", Player::Player1, 9)), ["e2", "e3", "e4", "e3v"]);
+    assert_eq!(names(get_opening_moves("Standard Opening", Player::Player1, 11)), ["f2", "f3", "f4", "f3v"]);
+    assert_eq!(names(get_opening_moves("Standard Opening", Player::Player2, 11)), ["f10", "f9", "f8", "f8v"]);
+    assert_eq!(names(get_opening_moves("Sidewall Opening", Player::Player1, 5)), ["a3h", "d3h"]);
+    // A line is cut short at the first move that falls off a small board
+    assert!(get_opening_moves("Sidewall Opening", Player::Player2, 5).is_empty());
+    assert_eq!(names(get_opening_moves("Ala Opening", Player::Player1, 5)), ["c2", "c3", "c4"]);
+}
+
+#[test]
+fn strategies_finish_games_on_every_size() {
+    for size in MIN_BOARD_SIZE..=MAX_BOARD_SIZE {
+        let tournament = Tournament::new(size, default_walls(size), 1, RuleSet::default());
+        let mut game = Quoridor::new(size, default_walls(size), RuleSet::default(), None).unwrap();
+        let mut players = [
+            tournament.create_strategy("ShortestPath", "Standard Opening", Player::Player1).unwrap(),
+            tournament.create_strategy("Mirror", "Standard Opening", Player::Player2).unwrap(),
+        ];
+        
+        let mut finished = false;
+        for _ in 0..400 {
+            let strategy = &mut players[game.active_player.index()];
+            let mv = strategy.choose_move(&game).expect("a move is always available");
+            if game.win_check(mv) {
+                finished = true;
+                break;
+            }
+            game.apply(mv).unwrap_or_else(|e| panic!("{}x{}: {}", size, size, e));
+        }
+        assert!(finished, "{}x{} game did not finish", size, size);
+    }
+}
@@ -0,0 +1,93 @@
+// Draws: threefold repetition of a position and the rules' move limit.
+use quoridor::main::*;
+
+fn pawn(notation: &str) -> Move {
+    notation.parse().unwrap()
+}
+
+fn play(game: &mut Quoridor, moves: &[&str]) {
+    for mv in moves {
+        game.apply(mv.parse().unwrap()).unwrap_or_else(|e| panic!("{}: {}", mv, e));
+    }
+}
+
+const SHUFFLE: [&str; 4] = ["e2", "e8", "e1", "e9"];
+
+#[test]
+fn a_third_repetition_is_a_draw() {
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    assert_eq!(game.repetitions(), 1);
+
+    play(&mut game, &SHUFFLE);
+    assert_eq!(game.repetitions(), 2);
+    assert_eq!(game.outcome(), None);
+
+    play(&mut game, &SHUFFLE[..3]);
+    assert_eq!(game.outcome(), None);
+    play(&mut game, &SHUFFLE[3..]);
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::Repetition)));
+    assert_eq!(game.apply(pawn("e2")), Err(QuoridorError::GameOver(GameResult::Draw(DrawReason::Repetition))));
+
+    game.undo();
+    assert_eq!(game.outcome(), None);
+    assert_eq!(game.repetitions(), 2);
+}
+
+fn key(state: &str) -> u64 {
+    Quoridor::new(9, 10, RuleSet::default(), Some(state)).unwrap().position_key()
+}
+
+#[test]
+fn positions_match_on_pawns_walls_counts_and_side_to_move() {
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    let start = game.position_key();
+    play(&mut game, &SHUFFLE);
+    assert_eq!(game.position_key(), start);
+    assert_eq!(key(" /  / e1 e9 / 10 10 / 1"), start);
+
+    assert_ne!(key(" /  / e1 e9 / 10 10 / 2"), start);
+    assert_ne!(key(" /  / e1 e8 / 10 10 / 1"), start);
+    assert_ne!(key(" /  / e1 e9 / 10 9 / 1"), start);
+    assert_ne!(key("a3 / c6 / e1 e9 / 8 10 / 1"), key("a3 / c6 / e1 e9 / 9 9 / 1"));
+    assert_ne!(key("a3 /  / e1 e9 / 9 10 / 1"), key(" / a3 / e1 e9 / 9 10 / 1"));
+}
+
+#[test]
+fn walls_reset_the_count_but_not_their_order() {
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    play(&mut game, &SHUFFLE);
+    play(&mut game, &["a3h"]);
+    assert_eq!(game.repetitions(), 1);
+
+    // The same walls put down the other way round give the same position
+    let mut one_way = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    let mut other_way = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    play(&mut one_way, &["a3h", "c6v", "e2"]);
+    play(&mut other_way, &["c6v", "a3h", "e2"]);
+    assert_eq!(one_way.position_key(), other_way.position_key());
+}
+
+#[test]
+fn the_move_limit_comes_from_the_rules() {
+    let rules = RuleSet { move_limit: 6, ..RuleSet::default() };
+    let mut game = Quoridor::new(9, 10, rules, None).unwrap();
+    play(&mut game, &["e2", "e8", "e3", "e7", "e4"]);
+    assert_eq!(game.outcome(), None);
+    play(&mut game, &["e6"]);
+    assert_eq!(game.outcome(), Some(GameResult::Draw(DrawReason::MoveLimit)));
+    assert_eq!(game.outcome().unwrap().to_string(), "draw by move limit");
+
+    // A win on the last move still counts as a win
+    let mut game = Quoridor::new(9, 10, RuleSet { move_limit: 1, ..rules }, Some(" /  / e8 e2 / 10 10 / 1")).unwrap();
+    play(&mut game, &["e9"]);
+    assert_eq!(game.outcome(), Some(GameResult::Win(Player::Player1)));
+}
+
+#[test]
+fn tournament_games_always_finish() {
+    // Two shuffling strategies can only end a game by the engine's rules
+    let rules = RuleSet { move_limit: 40, ..RuleSet::default() };
+    let mut tournament = Tournament::new(5, 0, 2, rules);
+    tournament.run_match("Random", "Random", "No Opening", false);
+}
@@ -0,0 +1,83 @@
+// Evaluators: the C3 features, path difference and weighted sums, their
+// names, and search strategies playing with any of them.
+use quoridor::main::evaluation::*;
+use quoridor::main::*;
+
+fn position() -> Quoridor {
+    Quoridor::new(9, 10, RuleSet::default(), Some("c3f6 / e3 / e4 e6 / 8 9 / 1")).unwrap()
+}
+
+#[test]
+fn evaluators_score_each_side() {
+    let game = position();
+    let path = PathDifferenceEvaluator;
+    let difference = game.distance_to_goal(Player::Player2) as f64 - game.distance_to_goal(Player::Player1) as f64;
+    assert_eq!(path.evaluate(&game, Player::Player1), difference);
+    assert_eq!(path.evaluate(&game, Player::Player2), -difference);
+
+    // C3 is the paper's weighted sum, and Minimax's default
+    let c3 = C3Evaluator::default();
+    let linear = WeightedLinearEvaluator::new(vec![
+        (0.6001, Feature::PathDifference),
+        (14.45, Feature::Attacking),
+        (6.52, Feature::Defensive),
+    ]);
+    for player in [Player::Player1, Player::Player2] {
+        assert!((c3.evaluate(&game, player) - linear.evaluate(&game, player)).abs() < 1e-9);
+    }
+    assert_eq!(MinimaxStrategy::new("", Vec::new(), 1, 0).evaluate(&game), c3.evaluate(&game, Player::Player1));
+    assert_eq!(Feature::WallDifference.value(&game, Player::Player1), -1.0);
+}
+
+#[test]
+fn evaluators_are_parsed_from_their_names() {
+    assert_eq!(parse_evaluator("C3").unwrap().name(), "C3");
+    assert_eq!(parse_evaluator("PathDifference").unwrap().name(), "PathDifference");
+
+    let name = "0.5*path_difference+-2*opponent_walls";
+    let linear: WeightedLinearEvaluator = name.parse().unwrap();
+    assert_eq!(linear.terms, vec![(0.5, Feature::PathDifference), (-2.0, Feature::OpponentWalls)]);
+    assert_eq!(linear.name(), name);
+    assert_eq!(parse_evaluator(name).unwrap().name(), name);
+
+    for feature in Feature::ALL {
+        assert_eq!(feature.to_string().parse::<Feature>(), Ok(feature));
+    }
+    for bad in ["", "c3", "2*speed", "path_difference", "x*path_difference", "1*path_difference+"] {
+        assert!(matches!(parse_evaluator(bad), Err(QuoridorError::UnknownEvaluator(_))), "{}", bad);
+    }
+}
+
+#[test]
+fn tournaments_give_strategies_the_evaluator_named() {
+    let tournament = Tournament::new(9, 10, 1, RuleSet::default());
+    for (name, expected) in [
+        ("Minimax2@PathDifference", "Minimax2@PathDifference"),
+        ("IDMinimax1s@C3", "IDMinimax1s@C3"),
+        ("Adaptive@1*wall_difference", "Adaptive@1*wall_difference"),
+        ("SimulatedAnnealing0.5@PathDifference", "SimulatedAnnealing0.5@PathDifference"),
+        ("MCTS1000@C3", "MCTS-1000@C3"),
+    ] {
+        assert_eq!(tournament.create_strategy(name, "", Player::Player1).unwrap().name(), expected);
+    }
+
+    // An evaluator that cannot be parsed is refused, not dropped
+    for name in ["Minimax2@Unknown", "Adaptive@1*bogus", "MCTS1000@"] {
+        assert!(tournament.create_strategy(name, "", Player::Player1).is_err(), "{}", name);
+    }
+
+    // The opening still comes last
+    let strategy = tournament.create_strategy("Minimax2@C3", "No Opening", Player::Player1).unwrap();
+    assert_eq!(strategy.name(), "Minimax2@C3-No Opening");
+}
+
+#[test]
+fn the_evaluator_decides_the_move() {
+    // An evaluator that counts falling behind as good walks away from the goal
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e3 e7 / 10 10 / 1")).unwrap();
+    let mut forward = MinimaxStrategy::new("", Vec::new(), 1, 0).with_evaluator(Box::new(PathDifferenceEvaluator));
+    let mut backward = MinimaxStrategy::new("", Vec::new(), 1, 0)
+        .with_evaluator(Box::new(WeightedLinearEvaluator::new(vec![(-1.0, Feature::PathDifference)])));
+    assert_eq!(forward.choose_move(&game), Some(Move::Pawn(Square::new(4, 4))));
+    assert_eq!(backward.choose_move(&game), Some(Move::Pawn(Square::new(4, 2))));
+}
@@ -0,0 +1,162 @@
+// Position features: shortest-path counts, disjoint paths, choke points,
+// the best single wall, race tempo with jumps and wall parity.
+use quoridor::main::evaluation::Feature;
+use quoridor::main::*;
+use rand::prelude::*;
+
+fn game(size: usize, rules: RuleSet, state: &str) -> Quoridor {
+    Quoridor::new(size, 10, rules, Some(state)).unwrap()
+}
+
+fn square(game: &Quoridor, name: &str) -> Coord {
+    game.algebraic_to_coord(name).unwrap()
+}
+
+#[test]
+fn an_open_file_is_one_long_corridor() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    assert_eq!(game.shortest_path_count(Player::Player1), 1);
+    assert_eq!(game.disjoint_shortest_paths(Player::Player1), 1);
+    let corridor: Vec<Coord> = (0..8).rev().map(|row| (row, 4)).collect();
+    assert_eq!(game.choke_points(Player::Player1), corridor);
+
+    // Any wall in front only costs a step sideways
+    assert_eq!(game.best_wall_damage(Player::Player1), 1);
+}
+
+#[test]
+fn paths_around_a_wall_meet_at_a_choke_point() {
+    // From c1, round the b2h wall by c2 or d1; both go on up from d2
+    let game = game(5, RuleSet::default(), "b2 /  / c1 c5 / 10 10 / 1");
+    assert_eq!(game.distance_to_goal(Player::Player1), 5);
+    assert_eq!(game.shortest_path_count(Player::Player1), 2);
+    assert_eq!(game.disjoint_shortest_paths(Player::Player1), 1);
+    let expected: Vec<Coord> = ["d2", "d3", "d4", "d5"].iter().map(|name| square(&game, name)).collect();
+    assert_eq!(game.choke_points(Player::Player1), expected);
+
+    // A long wall straight ahead leaves two ways round that share nothing
+    let rules = RuleSet { wall_length: 3, ..RuleSet::default() };
+    let game = self::game(9, rules, "d8 /  / e8 e2 / 10 10 / 1");
+    assert_eq!(game.shortest_path_count(Player::Player1), 2);
+    assert_eq!(game.disjoint_shortest_paths(Player::Player1), 2);
+    assert_eq!(game.choke_points(Player::Player1), Vec::new());
+}
+
+#[test]
+fn the_best_wall_is_found_among_the_legal_ones() {
+    for state in [
+        " /  / e1 e9 / 10 10 / 1",
+        "b2 /  / c1 c5 / 10 10 / 1",
+        "c3f6 / e3 / e4 e6 / 8 9 / 2",
+        "d7e2a5 / c5f4d2 / e5 d6 / 6 7 / 1",
+    ] {
+        let size = if state.contains("c5 /") { 5 } else { 9 };
+        let game = game(size, RuleSet::default(), state);
+        for target in [Player::Player1, Player::Player2] {
+            let before = game.distance_to_goal(target);
+            let brute_force = game.get_legal_walls(game.active_player).into_iter().map(|wall| {
+                let mut after = game.clone();
+                after.make_move(wall);
+                after.distance_to_goal(target) - before
+            }).max().unwrap_or(0);
+            assert_eq!(game.best_wall_damage(target), brute_force, "{} against {:?}", state, target);
+        }
+    }
+}
+
+#[test]
+fn races_count_jumps() {
+    // Level pegging, but player 2 jumps player 1 on the e-file and wins
+    // with player 1 a step short
+    let game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    assert_eq!(game.race_tempo(Player::Player1), -1);
+    assert_eq!(game.race_tempo(Player::Player2), 1);
+
+    // Player 1 is two steps from e9 while player 2 still needs eight
+    let game = self::game(9, RuleSet::default(), " /  / e7 a9 / 0 0 / 1");
+    assert_eq!(game.race_tempo(Player::Player1), 7);
+    assert_eq!(game.race_tempo(Player::Player2), -7);
+}
+
+// The race played out move by move on a copy of the game
+fn played_out_race(game: &Quoridor, player: Player) -> isize {
+    let mut race = game.clone();
+    while race.outcome().is_none() {
+        let mover = race.active_player;
+        let Some(step) = race.get_legal_moves(mover).into_iter().min_by_key(|mv| race.distance_from(mover, race.move_coord(*mv))) else {
+            return 0;
+        };
+        race.make_move(step);
+    }
+    match race.outcome() {
+        Some(GameResult::Win(winner)) if winner == player => race.players.iter()
+            .filter(|&&other| other != player)
+            .map(|&other| race.distance_to_goal(other) as isize)
+            .min()
+            .unwrap_or(0),
+        Some(GameResult::Win(_)) => -(race.distance_to_goal(player) as isize),
+        _ => 0,
+    }
+}
+
+#[test]
+fn features_match_playing_it_out_in_random_positions() {
+    let mut rng = StdRng::seed_from_u64(23);
+    let four = RuleSet { player_count: 4, ..RuleSet::default() };
+    for (rules, walls) in [(RuleSet::default(), 10), (four, 5)] {
+        for _ in 0..12 {
+            let mut game = Quoridor::new(9, walls, rules, None).unwrap();
+            for _ in 0..rng.gen_range(0..16) {
+                let moves = game.legal_moves();
+                let Some(&mv) = moves.iter().filter(|mv| !game.win_check(**mv)).choose(&mut rng) else {
+                    break;
+                };
+                game.make_move(mv);
+            }
+            game.update_state_string(true);
+            let state = &game.state_string;
+            
+            for &target in &game.players {
+                assert_eq!(game.race_tempo(target), played_out_race(&game, target), "{} for {}", state, target.name());
+                
+                // Only the walls the player to move could place are tried
+                if game.walls_available[&game.active_player] == 0 {
+                    continue;
+                }
+                let before = game.distance_to_goal(target);
+                let brute_force = game.get_legal_walls(game.active_player).into_iter().map(|wall| {
+                    let mut after = game.clone();
+                    after.make_move(wall);
+                    after.distance_to_goal(target) - before
+                }).max().unwrap_or(0);
+                assert_eq!(game.best_wall_damage(target), brute_force, "{} against {}", state, target.name());
+            }
+        }
+    }
+}
+
+#[test]
+fn wall_parity_says_who_places_the_last_wall() {
+    for (state, parity) in [
+        (" /  / e1 e9 / 10 10 / 1", -1),
+        (" /  / e1 e9 / 10 10 / 2", 1),
+        (" /  / e1 e9 / 3 2 / 1", 1),
+        (" /  / e1 e9 / 2 3 / 2", -1),
+        (" /  / e1 e9 / 0 0 / 1", 0),
+    ] {
+        let game = game(9, RuleSet::default(), state);
+        assert_eq!(game.wall_parity(Player::Player1), parity, "{}", state);
+        assert_eq!(game.wall_parity(Player::Player2), -parity, "{}", state);
+    }
+}
+
+#[test]
+fn evaluators_weigh_the_new_features() {
+    let game = game(9, RuleSet::default(), " /  / e1 e9 / 10 0 / 1");
+    assert_eq!(Feature::WallThreat.value(&game, Player::Player1), 0.0);
+    assert_eq!(Feature::WallOpportunity.value(&game, Player::Player1), 1.0);
+    assert_eq!(Feature::ChokePoints.value(&game, Player::Player1), 8.0);
+    assert_eq!(Feature::PathMultiplicity.value(&game, Player::Player1), 0.0);
+    assert_eq!(Feature::WallParity.value(&game, Player::Player1), 1.0);
+    assert_eq!("race_tempo".parse::<Feature>(), Ok(Feature::RaceTempo));
+}
@@ -0,0 +1,127 @@
+// The four-player variant: seats, goals and turn order, pawn moves with
+// several pawns adjacent, walls that must leave every player a path, and
+// complete games between the simpler strategies.
+use quoridor::main::evaluation::Feature;
+use quoridor::main::*;
+
+fn players(count: usize) -> RuleSet {
+    RuleSet { player_count: count, ..RuleSet::default() }
+}
+
+fn four_player(state: Option<&str>) -> Quoridor {
+    Quoridor::new(9, 5, players(4), state).unwrap()
+}
+
+fn sorted(moves: Vec<Move>) -> Vec<String> {
+    let mut names: Vec<String> = moves.into_iter().map(|mv| mv.to_string()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn pawns_start_on_all_four_edges() {
+    let game = four_player(None);
+    
+    assert_eq!(game.state_string, " /  / e1 e9 a5 i5 / 5 5 5 5 / 1");
+    assert_eq!(game.players, vec![Player::Player1, Player::Player3, Player::Player2, Player::Player4]);
+    for player in game.players.clone() {
+        assert_eq!(game.distance_to_goal(player), 8, "{}", player.name());
+    }
+    assert!(game.goal_positions[&Player::Player3].contains(&(4, 8)));
+    assert!(game.goal_positions[&Player::Player4].contains(&(4, 0)));
+}
+
+#[test]
+fn turns_go_clockwise_and_undo_follows() {
+    let mut game = four_player(None);
+    let order: Vec<Player> = (0..5).map(|_| {
+        let player = game.active_player;
+        let mv = game.get_legal_moves(player)[0];
+        game.apply(mv).unwrap();
+        player
+    }).collect();
+    
+    assert_eq!(order, [Player::Player1, Player::Player3, Player::Player2, Player::Player4, Player::Player1]);
+    game.undo();
+    assert_eq!(game.active_player, Player::Player1);
+    game.undo();
+    assert_eq!(game.active_player, Player::Player4);
+}
+
+#[test]
+fn player_and_wall_counts_are_validated() {
+    assert_eq!(Quoridor::new(9, 5, players(3), None).err(), Some(QuoridorError::UnsupportedPlayerCount(3)));
+    assert_eq!(Quoridor::new(9, 19, players(4), None).err(), Some(QuoridorError::TooManyWalls(19, 9)));
+    assert!(Quoridor::new(9, 5, players(4), Some(" /  / e1 e9 / 5 5 / 1")).is_err());
+}
+
+#[test]
+fn a_pawn_behind_the_pawn_in_front_forces_a_diagonal() {
+    // Player 1 on e4 faces player 3 on e5, with player 2 right behind on e6
+    let game = four_player(Some(" /  / e4 e6 e5 i5 / 5 5 5 5 / 1"));
+    assert_eq!(sorted(game.get_legal_moves(Player::Player1)), ["d4", "d5", "e3", "f4", "f5"]);
+    
+    // Player 4 beside the diagonal square takes it away
+    let game = four_player(Some(" /  / e4 e6 e5 f5 / 5 5 5 5 / 1"));
+    assert_eq!(sorted(game.get_legal_moves(Player::Player1)), ["d4", "d5", "e3", "f4"]);
+}
+
+#[test]
+fn walls_must_leave_every_player_a_path() {
+    // Player 3 in the corner behind a vertical wall has only the a-file out
+    let state = " / a1 / e1 e9 a1 i5 / 5 5 5 5 / 1";
+    let mut game = four_player(Some(state));
+    let wall: Move = "a2h".parse().unwrap();
+    
+    assert!(!game.get_legal_walls(Player::Player1).contains(&wall));
+    assert_eq!(game.apply(wall), Err(QuoridorError::PathBlocked(wall)));
+}
+
+#[test]
+fn simple_strategies_finish_four_player_games() {
+    let tournament = Tournament::new(9, 5, 1, players(4));
+    for strategy_names in [
+        ["ShortestPath", "Random", "Defensive", "ShortestPath"],
+        ["Defensive", "Defensive", "Random", "Random"],
+    ] {
+        let mut game = four_player(None);
+        let mut strategies: Vec<Box<dyn Strategy>> = [Player::Player1, Player::Player2, Player::Player3, Player::Player4]
+            .into_iter()
+            .zip(strategy_names)
+            .map(|(player, name)| tournament.create_strategy(name, "No Opening", player).unwrap())
+            .collect();
+        
+        let mut winner = None;
+        for _ in 0..5000 {
+            let player = game.active_player;
+            let mv = strategies[player.index()].choose_move(&game).expect("a move is always available");
+            if game.win_check(mv) {
+                winner = Some(player);
+                break;
+            }
+            game.apply(mv).unwrap_or_else(|e| panic!("{} played {}: {}", player.name(), mv, e));
+        }
+        assert!(winner.is_some(), "{:?} did not finish", strategy_names);
+    }
+}
+
+#[test]
+fn search_and_features_watch_the_leading_opponent() {
+    // Player 3 on h5 is a step from the i-file; the other two are far off
+    let game = four_player(Some(" /  / e4 e9 h5 i5 / 5 5 5 5 / 1"));
+    assert_eq!(game.leading_opponent(Player::Player1), Player::Player3);
+    assert_eq!(Feature::OpponentDistance.value(&game, Player::Player1), 1.0);
+    assert_eq!(Feature::PathDifference.value(&game, Player::Player1), -4.0);
+    
+    // Minimax walls player 3 off rather than player 2 across the board
+    let tournament = Tournament::new(9, 5, 1, players(4));
+    let mut minimax = tournament.create_strategy("Minimax2", "No Opening", Player::Player1).unwrap();
+    let mv = minimax.choose_move(&game).expect("a move is available");
+    let mut after = game.clone();
+    after.apply(mv).unwrap();
+    assert!(after.distance_to_goal(Player::Player3) > 1, "played {}", mv);
+    
+    let mut annealing = tournament.create_strategy("SimulatedAnnealing", "No Opening", Player::Player1).unwrap();
+    let mv = annealing.choose_move(&game).expect("a move is available");
+    assert!(game.clone().apply(mv).is_ok(), "played {}", mv);
+}
@@ -0,0 +1,62 @@
+// Game results: kept up to date by moves, take-backs and state strings, and
+// the end of play once a result is in.
+use quoridor::main::*;
+
+fn wall(notation: &str) -> Move {
+    notation.parse().unwrap()
+}
+
+#[test]
+fn reaching_the_goal_wins_and_ends_the_game() {
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e8 a2 / 10 10 / 1")).unwrap();
+    assert_eq!(game.outcome(), None);
+
+    game.apply(Move::Pawn(Square::new(4, 9))).unwrap();
+    assert_eq!(game.outcome(), Some(GameResult::Win(Player::Player1)));
+    assert_eq!(game.outcome().unwrap().winner(), Some(Player::Player1));
+    assert_eq!(game.outcome().unwrap().to_string(), "player1 wins");
+
+    let result = GameResult::Win(Player::Player1);
+    assert_eq!(game.apply(Move::Pawn(Square::new(0, 3))), Err(QuoridorError::GameOver(result)));
+    assert_eq!(game.apply(wall("b2h")), Err(QuoridorError::GameOver(result)));
+
+    // Taking the winning move back reopens the game
+    game.undo();
+    assert_eq!(game.outcome(), None);
+    game.redo();
+    assert_eq!(game.outcome(), Some(result));
+}
+
+#[test]
+fn walls_never_win() {
+    // A wall anchored on player 2's goal rank is not a pawn move there
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e8 a2 / 10 10 / 2")).unwrap();
+    game.apply(wall("a1h")).unwrap();
+    assert_eq!(game.last_move, Some(wall("a1h")));
+    assert_eq!(game.outcome(), None);
+}
+
+#[test]
+fn state_strings_carry_finished_games() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e5 e1 / 10 10 / 1")).unwrap();
+    assert_eq!(game.outcome(), Some(GameResult::Win(Player::Player2)));
+
+    let rules = RuleSet { player_count: 4, ..RuleSet::default() };
+    let mut game = Quoridor::new(9, 5, rules, Some(" /  / e1 e9 h5 i5 / 5 5 5 5 / 3")).unwrap();
+    assert_eq!(game.outcome(), None);
+    game.apply(Move::Pawn(Square::new(8, 6))).unwrap();
+    assert_eq!(game.outcome(), Some(GameResult::Win(Player::Player3)));
+}
+
+#[test]
+fn strategies_take_an_immediate_win() {
+    for name in ["Minimax2", "ShortestPath"] {
+        let tournament = Tournament::new(9, 10, 1, RuleSet::default());
+        let mut strategy = tournament.create_strategy(name, "No Opening", Player::Player1).unwrap();
+        let game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e8 e2 / 10 10 / 1")).unwrap();
+        let mv = strategy.choose_move(&game).unwrap();
+        let mut after = game.clone();
+        after.apply(mv).unwrap();
+        assert_eq!(after.outcome(), Some(GameResult::Win(Player::Player1)), "{} played {}", name, mv);
+    }
+}
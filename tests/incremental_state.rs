@@ -0,0 +1,78 @@
+// Cached distance fields kept across make_move and unmake_move, and wall
+// generation that only searches for walls across a shortest route: after
+// any random run of moves and take-backs, the game must answer exactly as
+// one set up from scratch from its state string.
+use quoridor::main::*;
+use rand::prelude::*;
+
+fn fresh(game: &mut Quoridor) -> Quoridor {
+    game.update_state_string(true);
+    Quoridor::new(game.size, game.walls, game.rules, Some(&game.state_string)).unwrap()
+}
+
+fn assert_same_distances(game: &Quoridor, scratch: &Quoridor) {
+    for &player in &game.players {
+        assert_eq!(game.distance_to_goal(player), scratch.distance_to_goal(player), "{} in {}", player.name(), scratch.state_string);
+        assert_eq!(game.moves_to_next_row(player), scratch.moves_to_next_row(player), "{} in {}", player.name(), scratch.state_string);
+    }
+}
+
+fn assert_same_walls(game: &Quoridor, scratch: &Quoridor) {
+    for &player in &game.players {
+        assert_eq!(game.get_legal_walls(player), scratch.get_legal_walls(player), "{} in {}", player.name(), scratch.state_string);
+    }
+    // Including the reasons for refusing the rest, and a wall is refused for
+    // the path exactly when, put down anyway, it leaves some pawn cut off
+    let player = game.active_player;
+    let mut probe = scratch.clone();
+    for row in 0..game.size {
+        for col in 0..game.size {
+            for orientation in [Orientation::Horizontal, Orientation::Vertical] {
+                let verdict = game.wall_check(player, (row, col), orientation);
+                let place = format!("{:?} {:?} in {}", (row, col), orientation, scratch.state_string);
+                assert_eq!(verdict, scratch.wall_check(player, (row, col), orientation), "{}", place);
+                if matches!(verdict, Ok(()) | Err(QuoridorError::PathBlocked(_))) {
+                    probe.make_move(Move::Wall(probe.square_of((row, col)), orientation));
+                    let cut_off = probe.players.iter().any(|&other| probe.distance_to_goal(other) == 100);
+                    probe.unmake_move();
+                    assert_eq!(cut_off, verdict.is_err(), "{}", place);
+                }
+            }
+        }
+    }
+}
+
+// Moves and take-backs at random, mostly forward, checking every position
+fn wander(size: usize, walls: usize, rules: RuleSet, seed: u64, check: impl Fn(&Quoridor, &Quoridor)) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut game = Quoridor::new(size, walls, rules, None).unwrap();
+    for _ in 0..300 {
+        let moves = game.legal_moves();
+        if game.history.is_empty() || (game.outcome().is_none() && !moves.is_empty() && rng.gen_bool(0.7)) {
+            // Walls first most of the time, so the board fills up
+            let walls: Vec<Move> = moves.iter().copied().filter(Move::is_wall).collect();
+            let pool = if !walls.is_empty() && rng.gen_bool(0.6) { &walls } else { &moves };
+            game.make_move(*pool.choose(&mut rng).unwrap());
+        } else {
+            game.unmake_move();
+        }
+        let scratch = fresh(&mut game);
+        check(&game, &scratch);
+    }
+}
+
+const VARIANTS: [(usize, usize, &str); 4] = [(5, 3, "standard"), (9, 10, "standard"), (9, 7, "long-walls"), (9, 5, "four-player")];
+
+#[test]
+fn distances_match_a_fresh_game_after_every_move_and_take_back() {
+    for (seed, (size, walls, rules)) in VARIANTS.into_iter().enumerate() {
+        wander(size, walls, rules.parse().unwrap(), seed as u64, assert_same_distances);
+    }
+}
+
+#[test]
+fn legal_walls_match_a_fresh_game_after_every_move_and_take_back() {
+    for (seed, (size, walls, rules)) in VARIANTS.into_iter().enumerate() {
+        wander(size, walls, rules.parse().unwrap(), 10 + seed as u64, assert_same_walls);
+    }
+}
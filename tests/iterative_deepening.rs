@@ -0,0 +1,74 @@
+// Iterative-deepening Minimax: budget names, staying within a time budget
+// and repeatable results under a node budget.
+use std::time::{Duration, Instant};
+
+use quoridor::main::*;
+
+#[test]
+fn budgets_are_named_in_seconds_milliseconds_or_nodes() {
+    for (name, limit) in [
+        ("2s", SearchLimit::Time(Duration::from_secs(2))),
+        ("250ms", SearchLimit::Time(Duration::from_millis(250))),
+        ("100kn", SearchLimit::Nodes(100_000)),
+        ("5500n", SearchLimit::Nodes(5500)),
+    ] {
+        assert_eq!(name.parse::<SearchLimit>(), Ok(limit));
+        assert_eq!(limit.to_string(), name);
+    }
+    assert_eq!("1.5s".parse::<SearchLimit>(), Ok(SearchLimit::Time(Duration::from_millis(1500))));
+    for bad in ["", "2", "s", "-1s", "twos", "10kb"] {
+        assert!(bad.parse::<SearchLimit>().is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn tournaments_create_iterative_deepening_by_name() {
+    let tournament = Tournament::new(9, 10, 1, RuleSet::default());
+    for name in ["IDMinimax2s", "IDMinimax500ms", "IDMinimax20kn"] {
+        assert_eq!(tournament.create_strategy(name, "No Opening", Player::Player1).unwrap().name(), name);
+    }
+}
+
+#[test]
+fn a_time_budget_is_kept() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some("c3f6 / e3 / e4 e6 / 8 9 / 1")).unwrap();
+    let mut strategy = MinimaxStrategy::iterative("", Vec::new(), SearchLimit::Time(Duration::from_millis(200)), 4);
+    let start = Instant::now();
+    let mv = strategy.choose_move(&game).expect("there are legal moves");
+    assert!(start.elapsed() < Duration::from_secs(1), "took {:?}", start.elapsed());
+    assert!(game.legal_moves().contains(&mv));
+}
+
+#[test]
+fn a_time_limited_search_stops_close_to_its_deadline() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some("c3f6 / e3 / e4 e6 / 8 9 / 1")).unwrap();
+
+    // The clock is read every 256 nodes inside the search, so allow for
+    // twice that many, however fast this build searches
+    let margin = MinimaxStrategy::iterative("", Vec::new(), SearchLimit::Nodes(512), 4).search(&game).time;
+
+    // Budgets that run out at every stage of an iteration, including just
+    // after one finishes, where another must not be started
+    for millis in [20, 35, 50, 80, 120, 200] {
+        let budget = Duration::from_millis(millis);
+        let mut strategy = MinimaxStrategy::iterative("", Vec::new(), SearchLimit::Time(budget), 4);
+        let info = strategy.search(&game);
+        assert!(info.time < budget + margin, "{:?} for a budget of {:?}, allowing {:?}", info.time, budget, margin);
+    }
+}
+
+#[test]
+fn a_node_budget_gives_the_same_move_every_time() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some("c3f6 / e3 / e4 e6 / 8 9 / 1")).unwrap();
+    for nodes in [500, 5_000, 20_000] {
+        let moves: Vec<Option<Move>> = (0..2)
+            .map(|_| MinimaxStrategy::iterative("", Vec::new(), SearchLimit::Nodes(nodes), 4).choose_move(&game))
+            .collect();
+        assert_eq!(moves[0], moves[1], "{} nodes", nodes);
+        assert!(game.legal_moves().contains(&moves[0].unwrap()));
+    }
+
+    // Even a budget too small for one ply gives a legal move
+    let mut starved = MinimaxStrategy::iterative("", Vec::new(), SearchLimit::Nodes(1), 4);
+    assert!(game.legal_moves().contains(&starved.choose_move(&game).unwrap()));
+}
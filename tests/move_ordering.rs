@@ -0,0 +1,59 @@
+// Move ordering: the table move, killers, route walls and forward steps
+// first, history within each rank, and fewer nodes for the search.
+use quoridor::main::ordering::MoveOrdering;
+use quoridor::main::*;
+
+fn wall(notation: &str) -> Move {
+    notation.parse().unwrap()
+}
+
+#[test]
+fn moves_are_ranked_by_what_they_do() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e4 e6 / 10 10 / 1")).unwrap();
+    let ordering = MoveOrdering::new();
+
+    // Player 2 walks straight down the e-file
+    let mut moves = vec![wall("a1h"), Move::Pawn(Square::new(4, 3)), wall("e5h"), Move::Pawn(Square::new(3, 4)), Move::Pawn(Square::new(4, 5))];
+    ordering.order(&game, &mut moves, None, 0);
+    assert_eq!(moves, vec![wall("e5h"), Move::Pawn(Square::new(4, 5)), Move::Pawn(Square::new(4, 3)), Move::Pawn(Square::new(3, 4)), wall("a1h")]);
+
+    ordering.order(&game, &mut moves, Some(wall("a1h")), 0);
+    assert_eq!(moves[0], wall("a1h"));
+}
+
+#[test]
+fn cutoffs_make_killers_and_history() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    let mut ordering = MoveOrdering::new();
+    ordering.record_cutoff(wall("a1h"), 2, 3);
+    ordering.record_cutoff(wall("h8v"), 2, 1);
+    ordering.record_cutoff(wall("h8v"), 2, 1);
+    ordering.record_cutoff(wall("c3h"), 0, 4);
+    assert_eq!(ordering.killers(2), [Some(wall("h8v")), Some(wall("a1h"))]);
+    assert_eq!(ordering.killers(1), [None, None]);
+    assert_eq!(ordering.history(wall("a1h")), 9);
+    assert_eq!(ordering.history(wall("h8v")), 2);
+
+    // Killers only count at their own ply; history counts everywhere
+    let mut moves = vec![wall("c3h"), wall("h8v"), wall("a1h")];
+    ordering.order(&game, &mut moves, None, 2);
+    assert_eq!(moves, vec![wall("a1h"), wall("h8v"), wall("c3h")]);
+    ordering.order(&game, &mut moves, None, 1);
+    assert_eq!(moves, vec![wall("c3h"), wall("a1h"), wall("h8v")]);
+
+    ordering.new_search();
+    assert_eq!(ordering.killers(2), [None, None]);
+    assert_eq!(ordering.history(wall("a1h")), 4);
+    assert_eq!(ordering.history(wall("h8v")), 1);
+}
+
+#[test]
+fn a_depth_three_search_stays_near_the_minimal_tree() {
+    // With perfect ordering alpha-beta visits about b^2 + b positions at
+    // depth three, b moves wide; every legal move is searched at the root
+    let game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    let width = game.legal_moves().len();
+    let info = MinimaxStrategy::new("", Vec::new(), 3, DEFAULT_TABLE_MB).search(&game);
+    assert!(info.nodes >= width, "{} nodes", info.nodes);
+    assert!(info.nodes < 3 * (width * width + width) / 2, "{} nodes for {} moves", info.nodes, width);
+}
@@ -0,0 +1,61 @@
+// Pawn moves under the official rules, one board configuration per row:
+// plain steps, straight jumps, and diagonal jumps when a wall or the board
+// edge is behind the opponent. Walls next to the opponent block diagonals.
+use quoridor::main::bitboard::BitboardGame;
+use quoridor::main::{Move, Quoridor, RuleSet};
+
+// (what the position shows, state string, expected moves for the side to move)
+const CASES: &[(&str, &str, &[&str])] = &[
+    ("opening position", " /  / e1 e9 / 10 10 / 1", &["d1", "e2", "f1"]),
+    ("open neighbours in the middle", " /  / e5 e9 / 10 10 / 1", &["d5", "e4", "e6", "f5"]),
+    ("wall in front", "e5 /  / e5 e9 / 10 10 / 1", &["d5", "e4", "f5"]),
+    ("wall beside", " / e5 / e5 e9 / 10 10 / 1", &["d5", "e4", "e6"]),
+    ("straight jump forward", " /  / e4 e5 / 10 10 / 1", &["d4", "e3", "e6", "f4"]),
+    ("straight jump backward", " /  / e4 e5 / 10 10 / 2", &["d5", "e3", "e6", "f5"]),
+    ("straight jump sideways", " /  / d5 e5 / 10 10 / 1", &["c5", "d4", "d6", "f5"]),
+    ("wall between pawns, no jump", "e4 /  / e4 e5 / 10 10 / 1", &["d4", "e3", "f4"]),
+    ("wall behind opponent, both diagonals", "e5 /  / e4 e5 / 10 10 / 1", &["d4", "d5", "e3", "f4", "f5"]),
+    ("wall behind opponent and on one side", "e5 / d5 / e4 e5 / 10 10 / 1", &["d4", "e3", "f4", "f5"]),
+    ("wall behind opponent and on both sides", "e5 / d5e4 / e4 e5 / 10 10 / 1", &["d4", "e3"]),
+    ("board edge behind opponent", " /  / e8 e9 / 10 10 / 1", &["d8", "d9", "e7", "f8", "f9"]),
+    ("board edge behind opponent, from below", " /  / e1 e2 / 10 10 / 2", &["d1", "d2", "e3", "f1", "f2"]),
+    ("board edge behind opponent sideways", " /  / h5 i5 / 10 10 / 1", &["g5", "h4", "h6", "i4", "i6"]),
+    ("board edge behind opponent in a corner", " /  / a8 a9 / 10 10 / 1", &["a7", "b8", "b9"]),
+    ("wall behind opponent sideways", " / e4 / d5 e5 / 10 10 / 1", &["c5", "d4", "d6", "e4", "e6"]),
+];
+
+fn sorted(moves: Vec<Move>) -> Vec<String> {
+    let mut names: Vec<String> = moves.into_iter().map(|mv| mv.to_string()).collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn pawn_moves_follow_official_rules() {
+    for &(description, state, expected) in CASES {
+        let game = Quoridor::new(9, 10, RuleSet::default(), Some(state)).unwrap();
+        let moves = sorted(game.get_legal_moves(game.active_player));
+        assert_eq!(moves, expected, "{}", description);
+    }
+}
+
+#[test]
+fn bitboard_pawn_moves_match() {
+    for &(description, state, expected) in CASES {
+        let game = Quoridor::new(9, 10, RuleSet::default(), Some(state)).unwrap();
+        let board = BitboardGame::from_quoridor(&game).unwrap();
+        let moves = sorted(board.get_legal_moves(board.active_player));
+        assert_eq!(moves, expected, "{}", description);
+    }
+}
+
+#[test]
+fn every_listed_move_can_be_played() {
+    for &(description, state, expected) in CASES {
+        for square in expected {
+            let mut game = Quoridor::new(9, 10, RuleSet::default(), Some(state)).unwrap();
+            let mv: Move = square.parse().unwrap();
+            assert_eq!(game.apply(mv), Ok(()), "{} to {}", description, square);
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// Pawn races once the walls are gone: solved exactly, checked against a
+// plain search of every line, and played from the solution by Minimax and
+// MCTS.
+use quoridor::main::endgame::PawnRaceCache;
+use quoridor::main::*;
+use rand::prelude::*;
+
+fn race(size: usize, state: &str) -> Quoridor {
+    Quoridor::new(size, 0, RuleSet::default(), Some(state)).unwrap()
+}
+
+// Whether the side to move can reach its goal within `plies` moves of both sides
+fn forced_win(game: &mut Quoridor, plies: usize) -> bool {
+    let moves = game.get_legal_moves(game.active_player);
+    plies >= 1 && moves.into_iter().any(|mv| {
+        if game.win_check(mv) {
+            return true;
+        }
+        game.make_move(mv);
+        let lost = forced_loss(game, plies - 1);
+        game.unmake_move();
+        lost
+    })
+}
+
+// Whether the side to move has lost within `plies` moves whatever it does
+fn forced_loss(game: &mut Quoridor, plies: usize) -> bool {
+    let moves = game.get_legal_moves(game.active_player);
+    moves.is_empty() || plies >= 2 && moves.into_iter().all(|mv| {
+        if game.win_check(mv) {
+            return false;
+        }
+        game.make_move(mv);
+        let won = forced_win(game, plies - 1);
+        game.unmake_move();
+        won
+    })
+}
+
+#[test]
+fn races_are_solved_only_without_walls() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    assert_eq!(game.solve_pawn_race(), None);
+    assert_eq!(Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e1 e9 / 0 1 / 1")).unwrap().solve_pawn_race(), None);
+    assert_eq!(race(9, " /  / e5 e1 / 0 0 / 1").solve_pawn_race(), None);
+
+    let rules = RuleSet { player_count: 4, ..RuleSet::default() };
+    let four = Quoridor::new(9, 0, rules, None).unwrap();
+    assert_eq!(four.solve_pawn_race(), None);
+}
+
+#[test]
+fn the_second_player_wins_the_open_race_by_jumping() {
+    // Level on distance, but player 2 meets player 1 in the middle and
+    // jumps over, so player 1 can only put the loss off
+    let game = race(9, " /  / e1 e9 / 0 0 / 1");
+    let solution = game.solve_pawn_race().unwrap();
+    assert_eq!(solution.result, GameResult::Win(Player::Player2));
+    assert_eq!(solution.plies(), solution.line.len());
+
+    let mut played = game.clone();
+    for &mv in &solution.line {
+        assert!(played.legal_moves().contains(&mv), "{} is not legal", mv);
+        played.apply(mv).unwrap();
+    }
+    assert_eq!(played.outcome(), Some(solution.result));
+
+    // One step from the goal is a win in one
+    let solution = race(9, " /  / e8 a5 / 0 0 / 1").solve_pawn_race().unwrap();
+    assert_eq!(solution.result, GameResult::Win(Player::Player1));
+    assert_eq!(solution.line, vec![Move::Pawn(Square::new(4, 9))]);
+}
+
+#[test]
+fn solutions_agree_with_searching_every_line() {
+    const HORIZON: usize = 9;
+    let mut rng = StdRng::seed_from_u64(24);
+    for _ in 0..20 {
+        // Spend both sides' walls at random, then wander the pawns
+        let mut game = Quoridor::new(5, 2, RuleSet::default(), None).unwrap();
+        while game.walls_available.values().any(|&left| left > 0) {
+            let walls = game.get_legal_walls(game.active_player);
+            game.apply(*walls.choose(&mut rng).unwrap()).unwrap();
+        }
+        for _ in 0..rng.gen_range(0..6) {
+            let moves = game.legal_moves();
+            let mv = *moves.choose(&mut rng).unwrap();
+            if game.win_check(mv) {
+                break;
+            }
+            game.apply(mv).unwrap();
+        }
+        if game.outcome().is_some() {
+            continue;
+        }
+
+        let solution = game.solve_pawn_race().unwrap();
+        let plies = solution.plies();
+        let state = game.state_string.clone();
+        match solution.result.winner() {
+            Some(winner) if winner == game.active_player && plies <= HORIZON => {
+                assert!(forced_win(&mut game, plies), "{}", state);
+                assert!(!forced_win(&mut game, plies - 1), "{}", state);
+            }
+            Some(_) if plies <= HORIZON => {
+                assert!(forced_loss(&mut game, plies), "{}", state);
+                assert!(!forced_loss(&mut game, plies - 1), "{}", state);
+            }
+            _ => {
+                assert!(!forced_win(&mut game, HORIZON), "{}", state);
+                assert!(!forced_loss(&mut game, HORIZON), "{}", state);
+            }
+        }
+    }
+}
+
+#[test]
+fn strategies_play_the_solution() {
+    let game = race(9, " /  / e1 e9 / 0 0 / 1");
+    let solution = game.solve_pawn_race().unwrap();
+
+    let mut minimax = MinimaxStrategy::new("", Vec::new(), 2, 4);
+    let info = minimax.search(&game);
+    assert_eq!(info.best_move, solution.best_move());
+    // Lost, as slowly as possible
+    assert_eq!(info.score, -(WIN_SCORE - solution.plies() as f64));
+    assert_eq!(info.pv, solution.line);
+    assert_eq!(info.nodes, 0);
+
+    let mut mcts = MCTSStrategy::new("", Vec::new(), 10);
+    assert_eq!(mcts.choose_move(&game), solution.best_move());
+}
+
+#[test]
+fn a_cached_solution_serves_the_whole_race() {
+    let mut cache = PawnRaceCache::new();
+    let mut game = race(9, " /  / e1 e9 / 0 0 / 1");
+    let solution = game.solve_pawn_race().unwrap();
+    for &mv in &solution.line {
+        assert_eq!(cache.solve(&game), game.solve_pawn_race());
+        game.apply(mv).unwrap();
+    }
+    assert_eq!(cache.solve(&game), None);
+
+    // New walls, or none of them, are solved afresh
+    for state in ["d8 /  / e2 e8 / 0 0 / 2", " /  / e2 e8 / 0 0 / 2", " /  / e2 e8 / 1 0 / 2"] {
+        let game = race(9, state);
+        assert_eq!(cache.solve(&game), game.solve_pawn_race(), "{}", state);
+    }
+}
+
+#[test]
+fn races_reached_inside_the_search_are_scored_exactly() {
+    // Player 1's last wall turns every line below it into a race
+    let mut game = race(9, " /  / e5 e6 / 1 0 / 1");
+    let mut minimax = MinimaxStrategy::new("", Vec::new(), 3, 4);
+    for mv in game.get_legal_walls(Player::Player1).into_iter().step_by(5) {
+        game.make_move(mv);
+        let solution = game.solve_pawn_race().unwrap();
+        let win = WIN_SCORE - (1 + solution.plies()) as f64;
+        let expected = match solution.result.winner() {
+            Some(winner) if winner == game.active_player => win,
+            Some(_) => -win,
+            None => 0.0,
+        };
+        // At the horizon and with depth to spare alike
+        for depth in [0, 2] {
+            let score = minimax.negamax(&mut game, depth, 1, f64::NEG_INFINITY, f64::INFINITY);
+            assert_eq!(score, expected, "{} searched to depth {}", mv, depth);
+        }
+        game.unmake_move();
+    }
+}
@@ -0,0 +1,64 @@
+// Zobrist position keys: updated move by move, they must always equal the
+// key of the same position set up from scratch, and come back on undo.
+use quoridor::main::*;
+use rand::prelude::*;
+
+fn from_scratch(game: &Quoridor) -> u64 {
+    Quoridor::new(game.size, game.walls, game.rules, Some(&game.state_string))
+        .unwrap()
+        .position_key()
+}
+
+// State string with the walls in a fixed order, as keys ignore the order
+fn canonical_state(game: &Quoridor) -> String {
+    let mut hwalls = game.hwall_positions.clone();
+    let mut vwalls = game.vwall_positions.clone();
+    hwalls.sort();
+    vwalls.sort();
+    let rest: Vec<&str> = game.state_string.split('/').skip(2).collect();
+    format!("{:?} {:?} {}", hwalls, vwalls, rest.join("/"))
+}
+
+#[test]
+fn incremental_keys_match_keys_from_scratch() {
+    let mut rng = StdRng::seed_from_u64(15);
+    for rules in ["standard", "shared-walls", "long-walls", "four-player"] {
+        let rules: RuleSet = rules.parse().unwrap();
+        for _ in 0..20 {
+            let mut game = Quoridor::new(7, 5, rules, None).unwrap();
+            let mut keys = vec![game.position_key()];
+            while game.outcome().is_none() {
+                let moves = game.legal_moves();
+                game.apply(*moves.choose(&mut rng).unwrap()).unwrap();
+                assert_eq!(game.position_key(), from_scratch(&game), "{}", game.state_string);
+                keys.push(game.position_key());
+            }
+
+            // Taking every move back retraces the same keys
+            keys.pop();
+            while game.undo().is_some() {
+                assert_eq!(game.position_key(), keys.pop().unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn keys_separate_positions() {
+    let mut seen = std::collections::HashMap::new();
+    let mut rng = StdRng::seed_from_u64(16);
+    for _ in 0..200 {
+        let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+        for _ in 0..rng.gen_range(0..30) {
+            let moves = game.legal_moves();
+            game.apply(*moves.choose(&mut rng).unwrap()).unwrap();
+            if game.outcome().is_some() {
+                break;
+            }
+        }
+        let state = canonical_state(&game);
+        if let Some(other) = seen.insert(game.position_key(), state.clone()) {
+            assert_eq!(other, state, "two positions share a key");
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// Rule variants: length-3 walls, a single goal square, random starting
+// squares and a shared wall pool, alone and combined, plus their names.
+use quoridor::main::bitboard::BitboardGame;
+use quoridor::main::*;
+
+fn long_walls() -> RuleSet {
+    RuleSet { wall_length: 3, ..RuleSet::default() }
+}
+
+fn wall(notation: &str) -> Move {
+    notation.parse().unwrap()
+}
+
+fn orientation(mv: Move) -> Orientation {
+    match mv {
+        Move::Wall(_, orientation) => orientation,
+        Move::Pawn(_) => panic!("{} is not a wall", mv),
+    }
+}
+
+#[test]
+fn rule_sets_are_named_by_their_variants() {
+    assert_eq!(RuleSet::default().to_string(), "standard");
+    assert_eq!("standard".parse::<RuleSet>(), Ok(RuleSet::default()));
+
+    let rules: RuleSet = "long-walls+single-goal+random-start+shared-walls".parse().unwrap();
+    assert_eq!(rules, RuleSet {
+        player_count: 2,
+        wall_length: 3,
+        goal: GoalRule::Square,
+        start: StartRule::RandomColumn,
+        shared_wall_pool: true,
+        move_limit: DEFAULT_MOVE_LIMIT,
+    });
+    assert_eq!(rules.to_string().parse::<RuleSet>(), Ok(rules));
+    assert!("longer-walls".parse::<RuleSet>().is_err());
+
+    let limited: RuleSet = "long-walls+move-limit-80".parse().unwrap();
+    assert_eq!(limited.move_limit, 80);
+    assert_eq!(limited.to_string(), "long-walls+move-limit-80");
+    assert!("move-limit-".parse::<RuleSet>().is_err());
+}
+
+#[test]
+fn long_walls_cut_three_edges_and_take_their_slots() {
+    let mut game = Quoridor::new(9, 10, long_walls(), None).unwrap();
+    assert_eq!(
+        game.get_wall_edges((5, 2), Orientation::Horizontal).unwrap(),
+        vec![((5, 2), (4, 2)), ((5, 3), (4, 3)), ((5, 4), (4, 4))]
+    );
+
+    // A horizontal wall must leave room for three squares along the row
+    assert_eq!(game.apply(wall("h3h")), Err(QuoridorError::OutOfBounds(wall("h3h"))));
+    assert_eq!(game.apply(wall("c8v")), Err(QuoridorError::OutOfBounds(wall("c8v"))));
+    assert!(!game.get_legal_walls(Player::Player1).contains(&wall("h3h")));
+    assert!(game.get_legal_walls(Player::Player1).contains(&wall("g3h")));
+
+    game.apply(wall("c4h")).unwrap();
+    game.apply(Move::Pawn(Square::new(5, 9))).unwrap();
+    for (overlapping, legal) in [
+        ("d4h", false), // Overlaps the last two squares
+        ("e4h", false), // Shares the last segment half
+        ("f4h", true),  // Meets the wall end to end
+        ("c4v", false), // Crosses the first post
+        ("d4v", false), // Crosses the second post
+        ("b4v", true),  // Touches the wall's end
+        ("e4v", true),  // Touches the wall's other end
+    ] {
+        let mv = wall(overlapping);
+        assert_eq!(game.wall_check(Player::Player1, game.move_coord(mv), orientation(mv)).is_ok(), legal, "{}", overlapping);
+        assert_eq!(game.get_legal_walls(Player::Player1).contains(&mv), legal, "{}", overlapping);
+    }
+
+    // Taking the wall back frees every slot it held
+    game.undo();
+    game.undo();
+    assert!(game.wall_check(Player::Player1, (5, 3), Orientation::Horizontal).is_ok());
+    assert!(game.wall_check(Player::Player1, (5, 3), Orientation::Vertical).is_ok());
+}
+
+#[test]
+fn a_single_goal_square_is_the_only_win() {
+    let rules = RuleSet { goal: GoalRule::Square, ..RuleSet::default() };
+    let game = Quoridor::new(9, 10, rules, Some(" /  / d8 e2 / 10 10 / 1")).unwrap();
+    assert_eq!(game.goal_positions[&Player::Player1], vec![(0, 4)]);
+    assert_eq!(game.goal_positions[&Player::Player2], vec![(8, 4)]);
+
+    // One step from the top edge, but two from e9
+    assert!(!game.win_check(Move::Pawn(Square::new(3, 9))));
+    assert_eq!(game.distance_to_goal(Player::Player1), 2);
+    assert_eq!(game.distance_to_goal(Player::Player2), 1);
+
+    // Walls may not seal off the square, even when the rest of the edge is open
+    let state = "d8 / e8 / a1 e5 / 10 10 / 1";
+    let mut edge_game = Quoridor::new(9, 10, RuleSet::default(), Some(state)).unwrap();
+    assert_eq!(edge_game.apply(wall("c8v")), Ok(()));
+    let mut square_game = Quoridor::new(9, 10, rules, Some(state)).unwrap();
+    assert!(!square_game.get_legal_walls(Player::Player1).contains(&wall("c8v")));
+    assert_eq!(square_game.apply(wall("c8v")), Err(QuoridorError::PathBlocked(wall("c8v"))));
+}
+
+#[test]
+fn random_starts_stay_on_each_players_edge() {
+    let rules = RuleSet { start: StartRule::RandomColumn, ..RuleSet::default() };
+    for _ in 0..50 {
+        let game = Quoridor::new(9, 10, rules, None).unwrap();
+        assert_eq!(game.pawn_positions[&Player::Player1].0, 8);
+        assert_eq!(game.pawn_positions[&Player::Player2].0, 0);
+
+        let four = Quoridor::new(5, 3, RuleSet { player_count: 4, ..rules }, None).unwrap();
+        assert_eq!(four.pawn_positions[&Player::Player3].1, 0);
+        assert_eq!(four.pawn_positions[&Player::Player4].1, 4);
+        let mut squares: Vec<Coord> = four.pawn_positions.values().copied().collect();
+        squares.sort();
+        squares.dedup();
+        assert_eq!(squares.len(), 4);
+    }
+
+    // Random starts alone still play on a bitboard; other variants do not
+    let game = Quoridor::new(9, 10, rules, None).unwrap();
+    assert!(BitboardGame::from_quoridor(&game).is_ok());
+    let game = Quoridor::new(9, 10, long_walls(), None).unwrap();
+    assert!(matches!(BitboardGame::from_quoridor(&game), Err(QuoridorError::UnsupportedRules(_))));
+}
+
+#[test]
+fn a_shared_pool_is_spent_by_either_player() {
+    let rules = RuleSet { shared_wall_pool: true, ..RuleSet::default() };
+    let mut game = Quoridor::new(5, 2, rules, None).unwrap();
+    assert_eq!(game.state_string, " /  / c1 c5 / 4 4 / 1");
+
+    for (mv, left) in [("a2h", 3), ("c4h", 2), ("a4v", 1), ("d2v", 0)] {
+        game.apply(wall(mv)).unwrap();
+        assert_eq!(game.walls_available[&Player::Player1], left);
+        assert_eq!(game.walls_available[&Player::Player2], left);
+    }
+    let active = game.active_player;
+    assert_eq!(game.apply(wall("b3h")), Err(QuoridorError::NoWallsLeft(active)));
+
+    game.undo();
+    assert_eq!(game.state_string, "a2c4 / a4 / c1 c5 / 1 1 / 2");
+
+    assert!(Quoridor::new(5, 2, rules, Some(" /  / c1 c5 / 4 3 / 1")).is_err());
+}
+
+#[test]
+fn wall_lengths_must_fit_the_board() {
+    let rules = RuleSet { wall_length: 5, ..RuleSet::default() };
+    assert!(matches!(Quoridor::new(5, 3, rules, None), Err(QuoridorError::UnsupportedRules(_))));
+    assert!(Quoridor::new(9, 10, rules, None).is_ok());
+}
+
+#[test]
+fn longer_walls_mean_fewer_fit() {
+    // 144 groove segments on 9x9: 36 two-square walls each, or 24 of three
+    assert_eq!(max_walls(9, RuleSet::default()), 36);
+    assert_eq!(max_walls(9, long_walls()), 24);
+    assert!(Quoridor::new(9, 24, long_walls(), None).is_ok());
+    assert_eq!(Quoridor::new(9, 25, long_walls(), None).err(), Some(QuoridorError::TooManyWalls(25, 9)));
+    assert!(Quoridor::new(9, 24, long_walls(), Some(" /  / e1 e9 / 24 25 / 1")).is_err());
+}
+
+#[test]
+fn tournaments_play_out_under_every_variant() {
+    for name in ["long-walls", "single-goal", "random-start", "shared-walls", "long-walls+single-goal+shared-walls"] {
+        let rules: RuleSet = name.parse().unwrap();
+        let tournament = Tournament::new(7, 6, 1, rules);
+        let mut game = Quoridor::new(7, 6, rules, None).unwrap();
+        let mut strategies = [
+            tournament.create_strategy("Defensive", "No Opening", Player::Player1).unwrap(),
+            tournament.create_strategy("Mirror", "No Opening", Player::Player2).unwrap(),
+        ];
+
+        let mut finished = false;
+        for _ in 0..2000 {
+            let player = game.active_player;
+            let mv = strategies[player.index()].choose_move(&game).expect("a move is always available");
+            if game.win_check(mv) {
+                finished = true;
+                break;
+            }
+            game.apply(mv).unwrap_or_else(|e| panic!("{}: {} played {}: {}", name, player.name(), mv, e));
+        }
+        assert!(finished, "{} did not finish", name);
+    }
+}
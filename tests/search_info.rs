@@ -0,0 +1,70 @@
+// Search reports: the principal variation, depth reached, node and table
+// counts, and what strategies that do not search report.
+use quoridor::main::*;
+
+fn position() -> Quoridor {
+    Quoridor::new(9, 10, RuleSet::default(), Some("c3f6 / e3 / e4 e6 / 8 9 / 1")).unwrap()
+}
+
+#[test]
+fn the_principal_variation_starts_with_the_move_played() {
+    let game = position();
+    let mut strategy = MinimaxStrategy::new("", Vec::new(), 3, DEFAULT_TABLE_MB);
+    assert_eq!(strategy.search_info(), None);
+
+    let mv = strategy.choose_move(&game).unwrap();
+    let info = strategy.search_info().expect("minimax reports its search");
+    assert_eq!(info.best_move, Some(mv));
+    assert_eq!(info.depth, 3);
+    assert!(info.nodes > 0);
+    assert!(info.table_hits > 0);
+    assert!(info.score.is_finite());
+
+    // Every move of the line is legal in turn
+    assert_eq!(info.pv.first(), Some(&mv));
+    assert!(info.pv.len() <= info.depth);
+    let mut line = game.clone();
+    for &mv in &info.pv {
+        line.apply(mv).unwrap_or_else(|e| panic!("{} in {:?}: {}", mv, info.pv, e));
+    }
+    assert!(info.to_string().starts_with("depth 3 score "));
+}
+
+#[test]
+fn no_table_means_no_table_hits() {
+    let mut strategy = MinimaxStrategy::new("", Vec::new(), 2, 0);
+    let info = strategy.search(&position());
+    assert_eq!(info.table_hits, 0);
+    assert_eq!(info.pv, info.best_move.into_iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn immediate_wins_and_deepening_are_reported() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e8 e2 / 10 10 / 1")).unwrap();
+    let info = MinimaxStrategy::new("", Vec::new(), 3, 1).search(&game);
+    assert_eq!(info.best_move, Some(Move::Pawn(Square::new(4, 9))));
+    assert_eq!(info.pv, vec![Move::Pawn(Square::new(4, 9))]);
+    // Scored as the search scores a win in one, whatever its depth
+    assert_eq!(info.score, WIN_SCORE - 1.0);
+    assert!(info.to_string().starts_with("depth 1 score 9999.00 "), "{}", info);
+
+    let mut strategy = MinimaxStrategy::iterative("", Vec::new(), SearchLimit::Nodes(3000), 1);
+    let info = strategy.search(&position());
+    assert!(info.depth >= 1);
+    assert!(info.nodes >= 3000);
+}
+
+#[test]
+fn a_depth_of_zero_searches_one_ply() {
+    let mut strategy = MinimaxStrategy::new("", Vec::new(), 0, 1);
+    assert_eq!(strategy.name(), "Minimax1");
+    assert!(strategy.choose_move(&position()).is_some());
+    assert_eq!(strategy.search_info().unwrap().depth, 1);
+}
+
+#[test]
+fn strategies_without_a_search_report_nothing() {
+    let mut strategy = ShortestPathStrategy::new("", Vec::new());
+    strategy.choose_move(&position());
+    assert_eq!(strategy.search_info(), None);
+}
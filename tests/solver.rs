@@ -0,0 +1,198 @@
+// Whole games solved on a 5x5 board: agreement with the pawn race solver and
+// with searching every line, one-move consistency, the table file, and the
+// Perfect strategy winning whatever the other side plays.
+use std::sync::Arc;
+
+use quoridor::main::bitboard::BitboardGame;
+use quoridor::main::solver::{SolutionTable, Value};
+use quoridor::main::*;
+use rand::prelude::*;
+
+// What the table says each move leads to, a finished game counting as a
+// loss for the side that did not get there
+fn outcomes(table: &SolutionTable, game: &BitboardGame) -> Vec<Value> {
+    game.legal_moves().into_iter()
+        .map(|mv| {
+            let mut after = *game;
+            after.make_move(mv);
+            table.value(&after).unwrap()
+        })
+        .collect()
+}
+
+// A position a few random moves into a game, mostly pawn moves so that some
+// walls are left; none if the game ends on the way
+fn random_position(rng: &mut StdRng, walls: usize) -> Option<BitboardGame> {
+    let mut game = BitboardGame::new(5, walls).unwrap();
+    for _ in 0..rng.gen_range(0..12) {
+        let moves = if rng.gen_bool(0.8) { game.get_legal_moves(game.active_player) } else { game.legal_moves() };
+        let mv = *moves.choose(rng)?;
+        if game.win_check(mv) {
+            return None;
+        }
+        game.make_move(mv);
+    }
+    Some(game)
+}
+
+fn forced_win(game: &BitboardGame, plies: usize) -> bool {
+    plies >= 1 && game.legal_moves().into_iter().any(|mv| {
+        let mut after = *game;
+        after.make_move(mv);
+        game.win_check(mv) || forced_loss(&after, plies - 1)
+    })
+}
+
+fn forced_loss(game: &BitboardGame, plies: usize) -> bool {
+    let moves = game.legal_moves();
+    moves.is_empty() || plies >= 2 && moves.into_iter().all(|mv| {
+        let mut after = *game;
+        after.make_move(mv);
+        !game.win_check(mv) && forced_win(&after, plies - 1)
+    })
+}
+
+#[test]
+fn without_walls_the_table_is_the_pawn_race() {
+    let table = SolutionTable::solve(5, 0).unwrap();
+    // Pawns on two different squares off their goals, and the side to move
+    assert_eq!(table.positions(), (20 * 20 - 15) * 2);
+
+    let mut board = BitboardGame::new(5, 0).unwrap();
+    for first in 0..20 {
+        for second in (5..25).filter(|&second| second != first) {
+            for player in [Player::Player1, Player::Player2] {
+                board.pawns = [first, second];
+                board.active_player = player;
+                let [one, two] = board.pawns.map(|square| Move::Pawn(board.square_at(square)));
+                let state = format!(" /  / {} {} / 0 0 / {}", one, two, player.index() + 1);
+                let race = Quoridor::new(5, 0, RuleSet::default(), Some(&state)).unwrap().solve_pawn_race().unwrap();
+
+                let expected = match race.result.winner() {
+                    Some(winner) if winner == player => Value::Win(race.plies()),
+                    Some(_) => Value::Loss(race.plies()),
+                    None => Value::Draw,
+                };
+                assert_eq!(table.value(&board), Some(expected), "{}", state);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_value_follows_from_the_moves_after_it() {
+    let table = SolutionTable::solve(5, 1).unwrap();
+    let mut rng = StdRng::seed_from_u64(25);
+    let mut checked = 0;
+    while checked < 300 {
+        let Some(game) = random_position(&mut rng, 1) else {
+            continue;
+        };
+        checked += 1;
+        let after = outcomes(&table, &game);
+        let quickest_win = after.iter().filter_map(|&value| match value {
+            Value::Loss(plies) => Some(plies + 1),
+            _ => None,
+        }).min();
+        let expected = match quickest_win {
+            Some(plies) => Value::Win(plies),
+            None if after.contains(&Value::Draw) => Value::Draw,
+            None => Value::Loss(after.iter().map(|&value| match value {
+                Value::Win(plies) => plies + 1,
+                _ => unreachable!(),
+            }).max().unwrap_or(0)),
+        };
+        assert_eq!(table.value(&game), Some(expected));
+    }
+}
+
+#[test]
+fn values_agree_with_searching_every_line() {
+    const HORIZON: usize = 5;
+    let table = SolutionTable::solve(5, 1).unwrap();
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut checked = 0;
+    while checked < 20 {
+        let Some(game) = random_position(&mut rng, 1) else {
+            continue;
+        };
+        checked += 1;
+        match table.value(&game).unwrap() {
+            Value::Win(plies) if plies <= HORIZON => {
+                assert!(forced_win(&game, plies));
+                assert!(!forced_win(&game, plies - 1));
+            },
+            Value::Loss(plies) if plies <= HORIZON => {
+                assert!(forced_loss(&game, plies));
+                assert!(!forced_loss(&game, plies - 1));
+            },
+            _ => {
+                assert!(!forced_win(&game, HORIZON));
+                assert!(!forced_loss(&game, HORIZON));
+            },
+        }
+    }
+}
+
+#[test]
+fn tables_survive_a_round_trip_through_a_file() {
+    let table = SolutionTable::solve(5, 1).unwrap();
+    let path = std::env::temp_dir().join(format!("quoridor-solution-{}.bin", std::process::id()));
+    table.save(&path).unwrap();
+    let loaded = SolutionTable::load(&path).unwrap();
+    assert_eq!((loaded.size(), loaded.walls(), loaded.positions()), (5, 1, table.positions()));
+
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..100 {
+        if let Some(game) = random_position(&mut rng, 1) {
+            assert_eq!(loaded.value(&game), table.value(&game));
+            assert_eq!(loaded.best_move(&game), table.best_move(&game));
+        }
+    }
+
+    // A cut-off file is refused, as is one from another format version or
+    // with a header that does not match its layers
+    let bytes = std::fs::read(&path).unwrap();
+    let refused = |bytes: &[u8]| {
+        std::fs::write(&path, bytes).unwrap();
+        SolutionTable::load(&path).err().map(|error| error.kind())
+    };
+    assert_eq!(refused(&bytes[..bytes.len() - 1]), Some(std::io::ErrorKind::InvalidData));
+    for (field, value) in [(4, 1), (5, 7), (6, 2)] {
+        let mut changed = bytes.clone();
+        changed[field] = value;
+        assert_eq!(refused(&changed), Some(std::io::ErrorKind::InvalidData), "byte {} set to {}", field, value);
+    }
+    assert_eq!(refused(&bytes), None);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn the_perfect_strategy_wins_a_won_game() {
+    let table = SolutionTable::solve(5, 1).unwrap();
+    let start = BitboardGame::new(5, 1).unwrap();
+    assert_eq!(table.value(&start), Some(Value::Loss(12)));
+    assert!(table.value(&BitboardGame::new(5, 2).unwrap()).is_none());
+
+    // Perfect needs a table to play from
+    let tournament = Tournament::new(5, 1, 1, RuleSet::default());
+    assert_eq!(tournament.create_strategy("Perfect", "", Player::Player2).err(), Some(QuoridorError::NoSolution));
+
+    let tournament = tournament.with_solution(Arc::new(table));
+    assert_eq!(tournament.create_strategy("Perfect", "", Player::Player2).unwrap().name(), "Perfect");
+    for opponent in ["Perfect", "Random", "ShortestPath", "Defensive", "Minimax2"] {
+        let mut strategies = [
+            tournament.create_strategy(opponent, "", Player::Player1).unwrap(),
+            tournament.create_strategy("Perfect", "", Player::Player2).unwrap(),
+        ];
+        let mut game = Quoridor::new(5, 1, RuleSet::default(), None).unwrap();
+        while game.outcome().is_none() {
+            let mv = strategies[game.active_player.index()].choose_move(&game).unwrap();
+            game.apply(mv).unwrap();
+        }
+        assert_eq!(game.outcome(), Some(GameResult::Win(Player::Player2)), "against {}", opponent);
+        if opponent == "Perfect" {
+            assert_eq!(game.history.len(), 12);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// State strings that describe impossible positions are refused with
+// MalformedState rather than set up as given.
+use quoridor::main::*;
+
+fn parse(state: &str) -> Result<Quoridor, QuoridorError> {
+    Quoridor::new(9, 10, RuleSet::default(), Some(state))
+}
+
+fn is_malformed(state: &str) -> bool {
+    matches!(parse(state), Err(QuoridorError::MalformedState(_)))
+}
+
+#[test]
+fn overlapping_walls_are_refused() {
+    assert!(is_malformed("d4c4 /  / e1 e9 / 10 10 / 1"));
+    assert!(is_malformed(" / d4d5 / e1 e9 / 10 10 / 1"));
+    assert!(parse("d4f4 /  / e1 e9 / 10 10 / 1").is_ok());
+}
+
+#[test]
+fn crossing_walls_are_refused() {
+    assert!(is_malformed("d4 / d4 / e1 e9 / 10 10 / 1"));
+    assert!(parse("d4 / c4 / e1 e9 / 10 10 / 1").is_ok());
+}
+
+#[test]
+fn walls_that_shut_a_pawn_in_are_refused() {
+    // Player 1 boxed into a1 and b1
+    assert!(is_malformed("a1 / b1 / a1 e9 / 10 10 / 1"));
+    // The same walls with the pawn outside them are fine
+    assert!(parse("a1 / b1 / e1 e9 / 10 10 / 1").is_ok());
+    // The fifth rank walled off but for a dead end up the i file
+    assert!(parse("a5c5e5g5 / h5 / e1 e9 / 10 10 / 1").is_ok());
+    assert!(is_malformed("a5c5e5g5h6 / h5 / e1 e9 / 10 10 / 1"));
+}
+
+#[test]
+fn two_pawns_on_one_square_are_refused() {
+    assert!(is_malformed(" /  / e5 e5 / 10 10 / 1"));
+    let rules = RuleSet { player_count: 4, ..RuleSet::default() };
+    let four = Quoridor::new(9, 5, rules, Some(" /  / e1 e9 a5 e1 / 5 5 5 5 / 1"));
+    assert!(matches!(four, Err(QuoridorError::MalformedState(_))));
+}
+
+#[test]
+fn more_walls_in_hand_than_fit_are_refused() {
+    assert!(is_malformed(" /  / e1 e9 / 99 10 / 1"));
+    assert!(is_malformed(" /  / e1 e9 / 10 37 / 1"));
+    assert!(parse(" /  / e1 e9 / 36 36 / 1").is_ok());
+
+    // A shared pool holds everyone's walls
+    let shared = RuleSet { shared_wall_pool: true, ..RuleSet::default() };
+    assert!(Quoridor::new(9, 10, shared, Some(" /  / e1 e9 / 72 72 / 1")).is_ok());
+    assert!(matches!(
+        Quoridor::new(9, 10, shared, Some(" /  / e1 e9 / 73 73 / 1")),
+        Err(QuoridorError::MalformedState(_))
+    ));
+}
+
+#[test]
+fn squares_off_the_board_are_refused() {
+    assert!(matches!(parse(" /  / e1 j9 / 10 10 / 1"), Err(QuoridorError::OutOfBounds(_))));
+    assert!(parse("a9 /  / e1 e9 / 10 10 / 1").is_err());
+    assert!(parse(" / i1 / e1 e9 / 10 10 / 1").is_err());
+}
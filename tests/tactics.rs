@@ -0,0 +1,52 @@
+// Tactical positions with a forced win that Minimax must find, whichever
+// side is to move, and that it prefers the quickest win.
+use quoridor::main::*;
+
+fn solve(state: &str, depth: usize) -> SearchInfo {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some(state)).unwrap();
+    MinimaxStrategy::new("", Vec::new(), depth, 4).search(&game)
+}
+
+fn wall(notation: &str) -> Move {
+    notation.parse().unwrap()
+}
+
+#[test]
+fn a_free_run_wins_in_two() {
+    // Player 2 has no walls to stop e8 then e9
+    let info = solve(" /  / e7 e5 / 10 0 / 1", 4);
+    assert_eq!(info.best_move, Some(Move::Pawn(Square::new(4, 8))));
+    assert_eq!(info.score, WIN_SCORE - 3.0);
+    assert_eq!(info.pv.len(), 3);
+}
+
+#[test]
+fn walling_off_a_threat_wins_the_race() {
+    // Player 2 wins next move from a2 unless a1h sends them round by c2,
+    // which gives player 1 time for e8 and e9
+    let info = solve(" /  / e7 a2 / 1 0 / 1", 5);
+    assert_eq!(info.best_move, Some(wall("a1h")));
+    assert!(info.score > WIN_BOUND, "score {}", info.score);
+
+    // The same race, mirrored, for player 2
+    let info = solve(" /  / a8 e3 / 0 1 / 2", 5);
+    assert_eq!(info.best_move, Some(wall("a8h")));
+    assert!(info.score > WIN_BOUND, "score {}", info.score);
+}
+
+#[test]
+fn a_lost_race_is_scored_as_lost() {
+    // Player 2 is a step from e1 and player 1 has no wall to stop them
+    let info = solve(" /  / e6 e2 / 0 10 / 1", 4);
+    assert_eq!(info.score, -(WIN_SCORE - 2.0));
+}
+
+#[test]
+fn iterative_deepening_finds_the_same_win() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some(" /  / e7 a2 / 1 0 / 1")).unwrap();
+    let mut strategy = MinimaxStrategy::iterative("", Vec::new(), SearchLimit::Nodes(200_000), 4);
+    let info = strategy.search(&game);
+    assert_eq!(info.best_move, Some(wall("a1h")));
+    assert!(info.depth >= 5);
+    assert!(info.score > WIN_BOUND, "score {}", info.score);
+}
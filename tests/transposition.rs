@@ -0,0 +1,59 @@
+// The transposition table: its memory budget, what it keeps, and that a
+// Minimax search using it picks the same moves as one without.
+use quoridor::main::transposition::{Bound, TranspositionTable};
+use quoridor::main::*;
+
+#[test]
+fn capacity_fits_the_memory_budget() {
+    let table = TranspositionTable::new(1);
+    assert!(table.capacity().is_power_of_two());
+    assert!(table.capacity() * std::mem::size_of::<Option<transposition::TableEntry>>() <= 1024 * 1024);
+    assert_eq!(TranspositionTable::new(4).capacity(), 4 * table.capacity());
+
+    // No memory, no table: nothing is kept
+    let mut empty = TranspositionTable::new(0);
+    assert_eq!(empty.capacity(), 0);
+    empty.store(7, 3, 1.0, Bound::Exact, None);
+    assert!(empty.probe(7).is_none());
+}
+
+#[test]
+fn deeper_results_from_the_same_search_are_kept() {
+    let mut table = TranspositionTable::new(1);
+    let key = 0x1234_5678;
+    let collision = key + table.capacity() as u64;
+    let mv: Move = "e2".parse().unwrap();
+
+    table.store(key, 3, 1.5, Bound::Lower, Some(mv));
+    let entry = table.probe(key).unwrap();
+    assert_eq!((entry.depth, entry.score, entry.bound, entry.best_move), (3, 1.5, Bound::Lower, Some(mv)));
+    assert!(table.probe(collision).is_none());
+
+    // A shallower result for another position sharing the slot gives way...
+    table.store(collision, 1, 0.0, Bound::Exact, None);
+    assert!(table.probe(collision).is_none());
+    assert!(table.probe(key).is_some());
+
+    // ...until the next search, when older entries make room
+    table.new_search();
+    table.store(collision, 1, 0.0, Bound::Exact, None);
+    assert!(table.probe(collision).is_some());
+    assert!(table.probe(key).is_none());
+
+    table.clear();
+    assert!(table.probe(collision).is_none());
+}
+
+#[test]
+fn the_table_does_not_change_the_move_chosen() {
+    for state in [
+        " /  / d1 d7 / 6 6 / 1",
+        "c3 / e4 / d3 d5 / 4 5 / 2",
+        "b2d5 / c3f2 / c4 e4 / 3 4 / 1",
+    ] {
+        let game = Quoridor::new(7, 6, RuleSet::default(), Some(state)).unwrap();
+        let mut plain = MinimaxStrategy::new("", Vec::new(), 3, 0);
+        let mut cached = MinimaxStrategy::new("", Vec::new(), 3, 4);
+        assert_eq!(cached.choose_move(&game), plain.choose_move(&game), "{}", state);
+    }
+}
@@ -0,0 +1,69 @@
+// Evaluation weights: their file format, Minimax evaluating with them, and
+// the self-play tuner that fits them to game results.
+use quoridor::main::tuning::{self, Sample, TuningConfig};
+use quoridor::main::*;
+use rand::prelude::*;
+
+#[test]
+fn weights_are_saved_and_loaded_by_name() {
+    let weights = EvalWeights { position_difference: 1.25, attacking: -3.0, defensive: 0.5 };
+    assert_eq!(weights.to_string().parse::<EvalWeights>(), Ok(weights));
+    assert_eq!("# tuned\ndefensive 6.52\n\nattacking 14.45\nposition_difference 0.6001\n".parse(), Ok(EvalWeights::PAPER));
+
+    let path = std::env::temp_dir().join(format!("quoridor-weights-{}.txt", std::process::id()));
+    weights.save(&path).unwrap();
+    assert_eq!(EvalWeights::load(&path).unwrap(), weights);
+    std::fs::remove_file(&path).unwrap();
+    assert!(EvalWeights::load(&path).is_err());
+
+    for bad in [
+        "attacking 14.45\ndefensive 6.52",
+        "position_difference 1\nposition_difference 2\nattacking 1\ndefensive 1",
+        "position_difference one\nattacking 1\ndefensive 1",
+        "position_difference inf\nattacking 1\ndefensive 1",
+        "speed 1\nposition_difference 1\nattacking 1\ndefensive 1",
+    ] {
+        assert!(matches!(bad.parse::<EvalWeights>(), Err(QuoridorError::MalformedWeights(_))), "{}", bad);
+    }
+}
+
+#[test]
+fn minimax_evaluates_with_its_weights() {
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some("c3f6 / e3 / e4 e6 / 8 9 / 1")).unwrap();
+    let features = evaluation::C3Evaluator::features(&game, Player::Player1);
+    let weights = EvalWeights { position_difference: 2.0, attacking: 0.0, defensive: -1.0 };
+    let minimax = MinimaxStrategy::new("", Vec::new(), 1, 0).with_weights(weights);
+    assert_eq!(minimax.evaluate(&game), 2.0 * features[0] - features[2]);
+
+    let paper = MinimaxStrategy::new("", Vec::new(), 1, 0);
+    let expected: f64 = features.iter().zip(EvalWeights::PAPER.to_array()).map(|(f, w)| f * w).sum();
+    assert!((paper.evaluate(&game) - expected).abs() < 1e-9);
+}
+
+#[test]
+fn spsa_fits_weights_to_results() {
+    // Results that only the position difference predicts
+    let mut rng = StdRng::seed_from_u64(7);
+    let samples: Vec<Sample> = (0..500).map(|_| {
+        let features = [rng.gen_range(-8.0..8.0), rng.gen_range(0.0..1.0), rng.gen_range(1.0..4.0)];
+        let result = if features[0] > 0.0 { 1.0 } else if features[0] < 0.0 { 0.0 } else { 0.5 };
+        Sample { features, result }
+    }).collect();
+
+    let start = EvalWeights::PAPER;
+    let scale = tuning::fit_scale(&samples, start);
+    let tuned = tuning::spsa(&samples, start, &TuningConfig::default(), &mut rng);
+    assert!(tuning::loss(&samples, tuned, scale) < 0.9 * tuning::loss(&samples, start, scale));
+    assert!(tuned.position_difference > 2.0 * start.position_difference, "{:?}", tuned);
+    assert!(tuned.defensive.abs() < start.defensive / 2.0, "{:?}", tuned);
+}
+
+#[test]
+fn self_play_samples_every_position_with_its_result() {
+    let tournament = Tournament::new(5, 3, 1, RuleSet::default());
+    let config = TuningConfig { games: 4, ..TuningConfig::default() };
+    let samples = tuning::self_play(&tournament, &config, &mut StdRng::seed_from_u64(3)).unwrap();
+    assert!(samples.len() >= 4 * 4, "{} samples", samples.len());
+    assert!(samples.iter().all(|sample| [0.0, 0.5, 1.0].contains(&sample.result)));
+    assert!(samples.iter().all(|sample| sample.features.iter().all(|f| f.is_finite())));
+}
@@ -0,0 +1,107 @@
+// Taking moves back and replaying them: undoing everything restores the
+// start exactly, redoing restores the end, and a new move drops the redo line.
+use quoridor::main::*;
+
+// What a position looks like from the outside, for comparing two of them
+#[derive(Debug, PartialEq)]
+struct Snapshot {
+    state: String,
+    legal_walls: Vec<Vec<Move>>,
+    distances: Vec<usize>,
+    walls_available: Vec<usize>,
+    key: u64,
+}
+
+fn snapshot(game: &Quoridor) -> Snapshot {
+    Snapshot {
+        state: game.state_string.clone(),
+        legal_walls: game.players.iter().map(|&player| game.get_legal_walls(player)).collect(),
+        distances: game.players.iter().map(|&player| game.distance_to_goal(player)).collect(),
+        walls_available: game.players.iter().map(|player| game.walls_available[player]).collect(),
+        key: game.position_key(),
+    }
+}
+
+const LINE: [&str; 8] = ["e2", "e8", "e3h", "d7v", "e3", "c6h", "f3", "e7"];
+
+fn play_line() -> (Quoridor, Snapshot, Snapshot) {
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    let start = snapshot(&game);
+    for mv in LINE {
+        game.apply(mv.parse().unwrap()).unwrap_or_else(|e| panic!("{}: {}", mv, e));
+    }
+    let end = snapshot(&game);
+    (game, start, end)
+}
+
+#[test]
+fn undoing_everything_restores_the_start() {
+    let (mut game, start, end) = play_line();
+    assert_ne!(start, end);
+
+    let mut undone = Vec::new();
+    while let Some(mv) = game.undo() {
+        undone.push(mv.to_string());
+    }
+    undone.reverse();
+    assert_eq!(undone, LINE);
+    assert_eq!(snapshot(&game), start);
+    assert!(game.history.is_empty());
+    assert_eq!(game.last_move, None);
+}
+
+#[test]
+fn redoing_everything_restores_the_end() {
+    let (mut game, _, end) = play_line();
+    while game.undo().is_some() {}
+
+    let mut redone = Vec::new();
+    while let Some(mv) = game.redo() {
+        redone.push(mv.to_string());
+    }
+    assert_eq!(redone, LINE);
+    assert_eq!(snapshot(&game), end);
+    assert_eq!(game.redo(), None);
+}
+
+#[test]
+fn undo_and_redo_step_one_move_at_a_time() {
+    let (mut game, _, end) = play_line();
+    game.undo();
+    game.undo();
+    let two_back = snapshot(&game);
+    game.redo();
+    game.undo();
+    assert_eq!(snapshot(&game), two_back);
+    game.redo();
+    game.redo();
+    assert_eq!(snapshot(&game), end);
+}
+
+#[test]
+fn a_new_move_clears_the_redo_line() {
+    let (mut game, _, _) = play_line();
+    game.undo();
+    game.undo();
+    assert_eq!(game.redo_stack.len(), 2);
+
+    // A wall and a pawn move both start a new line
+    game.apply("a1h".parse().unwrap()).unwrap();
+    assert!(game.redo_stack.is_empty());
+    assert_eq!(game.redo(), None);
+
+    game.undo();
+    assert_eq!(game.redo_stack.len(), 1);
+    game.apply("d3".parse().unwrap()).unwrap();
+    assert!(game.redo_stack.is_empty());
+    assert_eq!(game.redo(), None);
+}
+
+#[test]
+fn nothing_to_undo_or_redo_at_the_start() {
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    let start = snapshot(&game);
+    assert_eq!(game.undo(), None);
+    assert_eq!(game.redo(), None);
+    assert_eq!(snapshot(&game), start);
+}
@@ -0,0 +1,92 @@
+// Official overlap and crossing rules for walls. A wall blocks two squares'
+// worth of groove and has its centre on the post between them; a second
+// wall may not share a groove half (overlap) or the centre post with a
+// perpendicular wall (cross), but may touch end to end or in a T.
+use quoridor::main::{Move, Quoridor, QuoridorError, RuleSet, Square};
+
+// (wall already on the board, second wall, whether the second is legal)
+const CASES: &[(&str, &str, bool)] = &[
+    // Horizontal then horizontal
+    ("d4h", "d4h", false), // Same slot
+    ("d4h", "c4h", false), // Shifted left by one: shares the d-file half
+    ("d4h", "e4h", false), // Shifted right by one: shares the e-file half
+    ("d4h", "b4h", true),  // End to end on the left
+    ("d4h", "f4h", true),  // End to end on the right
+    ("d4h", "d3h", true),  // Parallel, one rank below
+    ("d4h", "d5h", true),  // Parallel, one rank above
+    // Horizontal then vertical
+    ("d4h", "d4v", false), // Crossing on the same centre post
+    ("d4h", "c4v", true),  // T against the left end
+    ("d4h", "e4v", true),  // T against the right end
+    ("d4h", "d3v", true),  // T from below into the centre
+    ("d4h", "d5v", true),  // T from above into the centre
+    // Vertical then vertical
+    ("d4v", "d4v", false), // Same slot
+    ("d4v", "d3v", false), // Shifted down by one: shares the rank 4 half
+    ("d4v", "d5v", false), // Shifted up by one: shares the rank 5 half
+    ("d4v", "d2v", true),  // End to end below
+    ("d4v", "d6v", true),  // End to end above
+    ("d4v", "c4v", true),  // Parallel, one file left
+    ("d4v", "e4v", true),  // Parallel, one file right
+    // Vertical then horizontal
+    ("d4v", "d4h", false), // Crossing on the same centre post
+    ("d4v", "d3h", true),  // T against the bottom end
+    ("d4v", "d5h", true),  // T against the top end
+    ("d4v", "c4h", true),  // T from the left into the centre
+    ("d4v", "e4h", true),  // T from the right into the centre
+];
+
+fn wall(notation: &str) -> Move {
+    notation.parse().expect("test walls are valid notation")
+}
+
+#[test]
+fn second_wall_follows_overlap_rules() {
+    for &(first, second, legal) in CASES {
+        let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+        game.apply(wall(first)).unwrap();
+        
+        let player = game.active_player;
+        let generated = game.get_legal_walls(player).contains(&wall(second));
+        let result = game.apply(wall(second));
+        
+        if legal {
+            assert_eq!(result, Ok(()), "{} after {}", second, first);
+        } else {
+            assert_eq!(result, Err(QuoridorError::WallOverlap(wall(second))), "{} after {}", second, first);
+        }
+        assert_eq!(generated, legal, "{} after {} in get_legal_walls", second, first);
+    }
+}
+
+#[test]
+fn taking_a_wall_back_frees_its_slots() {
+    for &(first, second, _) in CASES {
+        let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+        game.apply(wall(first)).unwrap();
+        game.undo();
+        game.apply(Move::Pawn(Square::new(4, 2))).unwrap(); // e2, handing the turn over as before
+        
+        assert_eq!(game.apply(wall(second)), Ok(()), "{} after {} was undone", second, first);
+    }
+}
+
+#[test]
+fn walls_in_a_state_string_take_their_slots() {
+    // Horizontal wall at d4, vertical wall at f6
+    let game = Quoridor::new(9, 10, RuleSet::default(), Some("d4 / f6 / e1 e9 / 9 9 / 1")).unwrap();
+    
+    for (notation, legal) in [("c4h", false), ("d4v", false), ("f5v", false), ("f6h", false), ("b4h", true), ("f4v", true)] {
+        assert_eq!(game.clone().apply(wall(notation)).is_ok(), legal, "{}", notation);
+    }
+}
+
+#[test]
+fn walls_must_fit_on_the_board() {
+    let mut game = Quoridor::new(9, 10, RuleSet::default(), None).unwrap();
+    
+    // A wall's anchor is its bottom-left square, so the last file and rank have no room
+    for notation in ["i4h", "d9v", "i9h"] {
+        assert_eq!(game.apply(wall(notation)), Err(QuoridorError::OutOfBounds(wall(notation))), "{}", notation);
+    }
+}